@@ -52,12 +52,18 @@ pub enum ContractError {
     CannotWithdraw {
         stream_status: StreamStatus,
     },
+    CannotPruneStream {
+        stream_status: StreamStatus,
+    },
     CliffNotPassed {
         timestamp: u64,
     },
     UnlockPeriodNotPassed {
         timestamp: u64,
     },
+    PrunePeriodNotPassed {
+        timestamp: u64,
+    },
     InvalidCommission,
     InsufficientGas {
         expected: Gas,
@@ -103,6 +109,23 @@ pub enum ContractError {
         #[serde(with = "b58_dec_format")]
         stream_id: CryptoHash,
     },
+    StreamFundedFromTreasury {
+        #[serde(with = "b58_dec_format")]
+        stream_id: CryptoHash,
+    },
+    CannotLockTreasuryStream,
+    CannotGateTreasuryStream,
+    CallerIsNotStreamApprover {
+        expected: Option<AccountId>,
+        received: AccountId,
+    },
+    InvalidMilestones {
+        max_milestones: usize,
+    },
+    NoMilestonesLeft {
+        #[serde(with = "b58_dec_format")]
+        stream_id: CryptoHash,
+    },
     DescriptionTooLong {
         max_description_len: usize,
         received: usize,
@@ -119,10 +142,152 @@ pub enum ContractError {
         #[serde(with = "u128_dec_format")]
         max_amount: Balance,
     },
+    InvalidBatchBalance {
+        #[serde(with = "u128_dec_format")]
+        expected: Balance,
+        #[serde(with = "u128_dec_format")]
+        received: Balance,
+    },
     PredecessorIsNotOwner {
         expected: AccountId,
         received: AccountId,
     },
+    AccountNotRegistered {
+        account_id: AccountId,
+    },
+    InsufficientStorageBalance {
+        #[serde(with = "u128_dec_format")]
+        requested: Balance,
+        #[serde(with = "u128_dec_format")]
+        left: Balance,
+    },
+    CannotUnregisterAccount {
+        account_id: AccountId,
+    },
+    SubscriptionNotExist {
+        #[serde(with = "b58_dec_format")]
+        subscription_id: CryptoHash,
+    },
+    CallerIsNotSubscriber {
+        expected: AccountId,
+        received: AccountId,
+    },
+    CallerIsNotMerchant {
+        expected: AccountId,
+        received: AccountId,
+    },
+    InvalidSubscriptionPeriod,
+    ChargePeriodNotPassed {
+        timestamp: u64,
+    },
+    CallerIsNotStreamReceiver {
+        expected: AccountId,
+        received: AccountId,
+    },
+    StreamNotPending {
+        stream_status: StreamStatus,
+    },
+    InvalidOperator,
+    TooManyOperators {
+        max_operators: usize,
+    },
+    OperatorNotExist {
+        operator_id: AccountId,
+    },
+    InvalidPayoutRoute,
+    CannotChainStream {
+        #[serde(with = "b58_dec_format")]
+        stream_id: CryptoHash,
+    },
+    ExceededMaxKeeperTip {
+        max_bps: u16,
+        #[serde(with = "u128_dec_format")]
+        max_flat: Balance,
+    },
+    InvalidCancelPolicy,
+    StopNotAllowedUntil {
+        timestamp: u64,
+    },
+    StopScheduled {
+        stop_at: u64,
+    },
+    CannotClawbackTreasuryStream,
+    ClawbackNotAllowed {
+        #[serde(with = "b58_dec_format")]
+        stream_id: CryptoHash,
+    },
+    StreamFrozen {
+        #[serde(with = "b58_dec_format")]
+        stream_id: CryptoHash,
+    },
+    StreamNotFrozen {
+        #[serde(with = "b58_dec_format")]
+        stream_id: CryptoHash,
+    },
+    InvalidArbiter,
+    NoArbiter {
+        #[serde(with = "b58_dec_format")]
+        stream_id: CryptoHash,
+    },
+    CallerIsNotStreamArbiter {
+        expected: Option<AccountId>,
+        received: AccountId,
+    },
+    CannotOpenDispute {
+        stream_status: StreamStatus,
+    },
+    StreamNotDisputed {
+        stream_status: StreamStatus,
+    },
+    InvalidDisputeSplit {
+        #[serde(with = "u128_dec_format")]
+        balance: Balance,
+    },
+    CannotSplitStream {
+        #[serde(with = "b58_dec_format")]
+        stream_id: CryptoHash,
+    },
+    InvalidSplitRatio,
+    CannotMergeStreams {
+        #[serde(with = "b58_dec_format")]
+        stream_id: CryptoHash,
+    },
+    InvalidMergedStreams {
+        max_merged_streams: usize,
+    },
+    StreamNftNotExist {
+        token_id: String,
+    },
+    StreamNftExists {
+        #[serde(with = "b58_dec_format")]
+        stream_id: CryptoHash,
+    },
+    StreamNftNotApproved {
+        account_id: AccountId,
+    },
+    TooManyStreamNftApprovals {
+        max_approvals: usize,
+    },
+    InvalidStreamNftReceiver,
+    CannotTransferStream {
+        stream_status: StreamStatus,
+    },
+    InvalidPledge,
+    CannotPledgeStream {
+        stream_status: StreamStatus,
+    },
+    StreamPledged {
+        #[serde(with = "b58_dec_format")]
+        stream_id: CryptoHash,
+    },
+    StreamNotPledged {
+        #[serde(with = "b58_dec_format")]
+        stream_id: CryptoHash,
+    },
+    CallerIsNotLender {
+        expected: AccountId,
+        received: AccountId,
+    },
     DataCorruption,
 }
 
@@ -4,6 +4,17 @@ pub const NO_DEPOSIT: Balance = 0;
 
 pub const MAX_DESCRIPTION_LEN: usize = 255;
 
+pub const MAX_MILESTONES: usize = 32;
+
+pub const MAX_OPERATORS: usize = 16;
+
+pub const MAX_MERGED_STREAMS: usize = 16;
+
+pub const MAX_STREAM_NFT_APPROVALS: usize = 16;
+
+pub const MAX_KEEPER_TIP_BPS: u16 = 100; // 1%
+pub const MAX_KEEPER_TIP_FLAT: Balance = ONE_NEAR / 100; // 0.01 NEAR
+
 pub const MIN_STREAMING_SPEED: u128 = 1;
 pub const MAX_STREAMING_SPEED: u128 = 10u128.pow(27 as _); // 1e27
 
@@ -17,8 +28,42 @@ pub const ONE_TERA: u64 = Gas::ONE_TERA.0; // near-sdk Gas is totally useless
 pub const DEFAULT_COMMISSION_NON_PAYMENT_FT: Balance = ONE_NEAR / 10; // 0.1 NEAR
 
 pub const DEFAULT_VIEW_STREAMS_LIMIT: u32 = 10;
+pub const MAX_VIEW_STREAMS_SCAN: u32 = 500;
 pub const STORAGE_NEEDS_PER_STREAM: Balance = ONE_NEAR / 20; // 0.05 NEAR
 
+// Minimal NEP-145 storage balance for an account to be registered.
+// It covers the account record itself, so streams created by the account
+// are charged on top of it by actual storage usage.
+pub const MIN_STORAGE_BALANCE: Balance = ONE_NEAR / 100; // 0.01 NEAR
+
+// Terminated streams may be pruned only after the period passed,
+// so both parties have enough time to see how the stream finished.
+pub const DEFAULT_STREAM_PRUNE_PERIOD_SEC: u64 = 60 * 60 * 24 * 30; // 30 days
+
+// Daily stats of tokens are kept in a ring buffer,
+// so the bucket of a day is overwritten a year later.
+pub const STATS_HISTORY_DAYS: u32 = 365;
+pub const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+
+pub const GAS_FOR_FT_BALANCE_OF: Gas = Gas(10 * ONE_TERA);
+pub const GAS_FOR_ON_RECONCILE_TVL: Gas = Gas(10 * ONE_TERA);
+
+// Transferring back the stream NFT rejected by the receiver
+// withdraws the stream, so the payment is made once more.
+pub const GAS_FOR_NFT_ON_TRANSFER: Gas = Gas(25 * ONE_TERA);
+pub const GAS_FOR_NFT_RESOLVE_TRANSFER: Gas = Gas(100 * ONE_TERA);
+pub const GAS_FOR_NFT_ON_APPROVE: Gas = Gas(10 * ONE_TERA);
+
+// Gas attached to transfers through finance contract,
+// routed payouts cover the call, the callback and the fallback transfer.
+pub const GAS_FOR_FT_TRANSFER_FROM_FINANCE: Gas = Gas(50 * ONE_TERA);
+pub const GAS_FOR_PAYOUT_FROM_FINANCE: Gas = Gas(100 * ONE_TERA);
+
+// Gas needed to process a stream within batch calls besides the transfers.
+// Pausing makes a payment and stopping may pay the severance and make a refund as well,
+// see `Contract::gas_for_batch` for the worst case of the stream.
+pub const GAS_FOR_BATCH_START: Gas = Gas(10 * ONE_TERA);
+
 // Explanation on default storage balance and gas needs.
 //
 // Normally it's enough to take 0.00125 NEAR for storage deposit
@@ -50,7 +95,7 @@ pub const DEFAULT_ROKE_DERIVATIVE_UNLOCK_PERIOD: u64 = 60 * 60 * 24;
 
 #[ext_contract(ext_finance)]
 pub trait ExtFinance {
-    fn streaming_storage_needs_transfer(&mut self) -> Promise;
+    fn streaming_storage_needs_transfer(&mut self, streams: Option<u32>) -> Promise;
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Debug, Clone)]
@@ -130,17 +175,32 @@ pub fn check_integrity(action_result: bool) -> Result<(), ContractError> {
 }
 
 pub type StreamId = CryptoHash;
+pub type SubscriptionId = CryptoHash;
 
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Debug)]
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(crate = "near_sdk::serde")]
 pub enum StreamStatus {
     Initialized,
     Active,
     Paused,
-    Finished { reason: StreamFinishReason },
+    Finished {
+        reason: StreamFinishReason,
+    },
+    // Awaits acceptance of the receiver, see `Account::require_accept`.
+    // Commission taken on creation is kept to refund it on rejection,
+    // it's in NEAR for non-payment tokens.
+    Pending {
+        is_auto_start_enabled: bool,
+        #[serde(with = "u128_dec_format")]
+        commission: Balance,
+    },
+    // Nothing is accrued or withdrawn until the arbiter resolves the dispute
+    Disputed {
+        opened_by: AccountId,
+    },
 }
 
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Debug)]
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(crate = "near_sdk::serde")]
 pub enum StreamFinishReason {
     StoppedByOwner,
@@ -148,14 +208,22 @@ pub enum StreamFinishReason {
     FinishedNaturally,
     FinishedBecauseCannotBeExtended,
     FinishedWhileTransferred,
+    FinishedBecauseTreasuryExhausted,
+    RejectedByApprover,
+    RejectedByReceiver,
+    ClawedBack,
+    ResolvedByArbiter,
+    MergedIntoStream,
 }
 
 impl StreamStatus {
     pub fn is_terminated(&self) -> bool {
         match self {
+            StreamStatus::Pending { .. } => false,
             StreamStatus::Initialized => false,
             StreamStatus::Active => false,
             StreamStatus::Paused => false,
+            StreamStatus::Disputed { .. } => false,
             StreamStatus::Finished { reason: _ } => true,
         }
     }
@@ -167,8 +235,10 @@ pub enum ActionType {
     Init,
     Start,
     Pause,
-    Withdraw,
+    // Keeper is the account withdrawing on behalf of the receiver
+    Withdraw { keeper_id: Option<AccountId> },
     Stop { reason: StreamFinishReason },
+    Dispute { opened_by: AccountId },
 }
 
 pub mod u128_dec_format {
@@ -192,6 +262,31 @@ pub mod u128_dec_format {
     }
 }
 
+pub mod u128_vec_format {
+    use near_sdk::json_types::U128;
+    use near_sdk::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S>(vec: &[u128], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        vec.iter()
+            .map(|num| U128(*num))
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u128>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<U128>::deserialize(deserializer)?
+            .into_iter()
+            .map(|num| num.0)
+            .collect())
+    }
+}
+
 pub mod b58_dec_format {
     use near_sdk::json_types::Base58CryptoHash;
     use near_sdk::serde::{Deserialize, Deserializer, Serializer};
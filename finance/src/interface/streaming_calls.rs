@@ -14,10 +14,27 @@ impl Contract {
         self.ft_transfer(token_account_id, receiver, amount.into())
     }
 
+    // Transfers tokens along the payout route of the receiver,
+    // the receiver is the fallback if the route fails.
     #[payable]
-    pub fn streaming_storage_needs_transfer(&mut self) -> Promise {
+    pub fn streaming_ft_transfer_routed(
+        &mut self,
+        token_account_id: AccountId,
+        receiver: AccountId,
+        amount: U128,
+        msg: Option<String>,
+        fallback_id: AccountId,
+    ) -> Promise {
+        self.check_owner().unwrap();
+
+        self.ft_transfer_routed(token_account_id, receiver, amount.into(), msg, fallback_id)
+    }
+
+    #[payable]
+    pub fn streaming_storage_needs_transfer(&mut self, streams: Option<u32>) -> Promise {
         self.check_owner().unwrap();
 
-        Promise::new(self.owner_id.clone()).transfer(STORAGE_NEEDS_PER_STREAM)
+        Promise::new(self.owner_id.clone())
+            .transfer(STORAGE_NEEDS_PER_STREAM * streams.unwrap_or(1) as Balance)
     }
 }
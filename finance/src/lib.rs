@@ -1,5 +1,6 @@
 mod interface;
 mod transfer;
+mod unit_tests;
 
 pub use crate::transfer::*;
 
@@ -10,8 +11,14 @@ use near_sdk::json_types::U128;
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
     env, ext_contract, near_bindgen, AccountId, Balance, BorshStorageKey, Gas, PanicOnDefault,
-    Promise, ONE_YOCTO,
+    Promise, PromiseResult, ONE_YOCTO,
 };
+use std::cmp::min;
+
+const GAS_FOR_ROUTED_FT_TRANSFER: Gas = Gas(Gas::ONE_TERA.0 * 10);
+const GAS_FOR_ROUTED_FT_TRANSFER_CALL: Gas = Gas(Gas::ONE_TERA.0 * 40);
+// Enough to send unused tokens to the fallback account
+const GAS_FOR_ON_ROUTED_TRANSFER: Gas = Gas(Gas::ONE_TERA.0 * 30);
 
 pub use common::*;
 
@@ -5,6 +5,13 @@ use crate::*;
 #[ext_contract(ext_self)]
 pub trait ExtTransferUnwrapped {
     fn on_near_unwrapped(&mut self, account_id: AccountId, amount: U128) -> Promise;
+    fn on_routed_transfer(
+        &mut self,
+        token_account_id: AccountId,
+        fallback_id: AccountId,
+        amount: U128,
+        is_call: bool,
+    ) -> Option<Promise>;
 }
 
 #[near_bindgen]
@@ -13,9 +20,64 @@ impl Contract {
     pub fn on_near_unwrapped(&mut self, account_id: AccountId, amount: U128) -> Promise {
         Promise::new(account_id).transfer(amount.into())
     }
+
+    // Tokens not accepted by the destination are sent to the fallback account.
+    #[private]
+    pub fn on_routed_transfer(
+        &mut self,
+        token_account_id: AccountId,
+        fallback_id: AccountId,
+        amount: U128,
+        is_call: bool,
+    ) -> Option<Promise> {
+        let amount: Balance = amount.into();
+        let unused = match env::promise_result(0) {
+            PromiseResult::Successful(value) if is_call => {
+                // ft_transfer_call returns the amount used by the destination
+                near_sdk::serde_json::from_slice::<U128>(&value)
+                    .map_or(0, |used| amount - min(used.0, amount))
+            }
+            PromiseResult::Successful(_) => 0,
+            _ => amount,
+        };
+        if unused == 0 {
+            return None;
+        }
+        Some(self.ft_transfer(token_account_id, fallback_id, unused))
+    }
 }
 
 impl Contract {
+    // Transfers tokens to the destination, with `ft_transfer_call` if `msg` is set.
+    // If the transfer fails, tokens are sent to the fallback account.
+    //
+    // The callback takes fixed gas, the rest of unused gas goes to the transfer.
+    pub(crate) fn ft_transfer_routed(
+        &self,
+        token_account_id: AccountId,
+        receiver: AccountId,
+        amount: Balance,
+        msg: Option<String>,
+        fallback_id: AccountId,
+    ) -> Promise {
+        let is_call = msg.is_some();
+        let transfer = ext_ft_core::ext(token_account_id.clone()).with_attached_deposit(ONE_YOCTO);
+        let promise = match msg {
+            Some(msg) => transfer
+                .with_static_gas(GAS_FOR_ROUTED_FT_TRANSFER_CALL)
+                .ft_transfer_call(receiver, U128(amount), None, msg),
+            None => transfer
+                .with_static_gas(GAS_FOR_ROUTED_FT_TRANSFER)
+                .ft_transfer(receiver, U128(amount), None),
+        };
+        promise.then(
+            ext_self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_ON_ROUTED_TRANSFER)
+                .with_unused_gas_weight(0)
+                .on_routed_transfer(token_account_id, fallback_id, U128(amount), is_call),
+        )
+    }
+
     pub(crate) fn ft_transfer(
         &self,
         token_account_id: AccountId,
@@ -0,0 +1,131 @@
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use crate::*;
+    use near_sdk::mock::VmAction;
+    use near_sdk::serde_json::{self, Value};
+    use near_sdk::test_utils::test_env::{alice, bob};
+    use near_sdk::test_utils::{get_created_receipts, VMContextBuilder};
+    use near_sdk::{testing_env, RuntimeFeesConfig, VMConfig};
+
+    fn streaming_id() -> AccountId {
+        "streaming.near".parse().unwrap()
+    }
+    fn finance_id() -> AccountId {
+        "finance.near".parse().unwrap()
+    }
+    fn token_id() -> AccountId {
+        "token.near".parse().unwrap()
+    }
+
+    fn new_contract() -> Contract {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(finance_id())
+            .predecessor_account_id(finance_id())
+            .build());
+        Contract::new(streaming_id())
+    }
+
+    fn set_promise_result(result: PromiseResult) {
+        testing_env!(
+            VMContextBuilder::new()
+                .current_account_id(finance_id())
+                .predecessor_account_id(finance_id())
+                .build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+    }
+
+    // Function calls made by the contract as (receiver, method, args, gas)
+    fn created_calls() -> Vec<(AccountId, String, Value, Gas)> {
+        get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| {
+                receipt
+                    .actions
+                    .into_iter()
+                    .filter_map(move |action| match action {
+                        VmAction::FunctionCall {
+                            function_name,
+                            args,
+                            gas,
+                            ..
+                        } => Some((
+                            receipt.receiver_id.clone(),
+                            function_name,
+                            serde_json::from_slice(&args).unwrap(),
+                            gas,
+                        )),
+                        _ => None,
+                    })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ft_transfer_routed_gas() {
+        let contract = new_contract();
+        drop(contract.ft_transfer_routed(token_id(), alice(), 10, None, bob()));
+        let calls = created_calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].0, token_id());
+        assert_eq!(calls[0].1, "ft_transfer");
+        assert_eq!(calls[0].2["receiver_id"], "alice.near");
+        assert_eq!(calls[0].3, GAS_FOR_ROUTED_FT_TRANSFER);
+        // The callback takes fixed gas regardless of the gas attached
+        assert_eq!(calls[1].0, finance_id());
+        assert_eq!(calls[1].1, "on_routed_transfer");
+        assert_eq!(calls[1].2["fallback_id"], "bob.near");
+        assert_eq!(calls[1].2["is_call"], false);
+        assert_eq!(calls[1].3, GAS_FOR_ON_ROUTED_TRANSFER);
+
+        let contract = new_contract();
+        drop(contract.ft_transfer_routed(token_id(), alice(), 10, Some("msg".to_string()), bob()));
+        let calls = created_calls();
+        assert_eq!(calls[0].1, "ft_transfer_call");
+        assert_eq!(calls[0].2["msg"], "msg");
+        assert_eq!(calls[0].3, GAS_FOR_ROUTED_FT_TRANSFER_CALL);
+        assert_eq!(calls[1].2["is_call"], true);
+        assert_eq!(calls[1].3, GAS_FOR_ON_ROUTED_TRANSFER);
+    }
+
+    #[test]
+    fn test_on_routed_transfer_success() {
+        let mut contract = new_contract();
+        set_promise_result(PromiseResult::Successful(vec![]));
+        assert!(contract
+            .on_routed_transfer(token_id(), bob(), U128(10), false)
+            .is_none());
+
+        // All tokens are used by the destination
+        set_promise_result(PromiseResult::Successful(b"\"10\"".to_vec()));
+        assert!(contract
+            .on_routed_transfer(token_id(), bob(), U128(10), true)
+            .is_none());
+        assert!(created_calls().is_empty());
+    }
+
+    #[test]
+    fn test_on_routed_transfer_fallback() {
+        let mut contract = new_contract();
+        // Unused tokens of ft_transfer_call are sent to the fallback account
+        set_promise_result(PromiseResult::Successful(b"\"4\"".to_vec()));
+        drop(contract.on_routed_transfer(token_id(), bob(), U128(10), true));
+        let calls = created_calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].0, token_id());
+        assert_eq!(calls[0].1, "ft_transfer");
+        assert_eq!(calls[0].2["receiver_id"], "bob.near");
+        assert_eq!(calls[0].2["amount"], "6");
+
+        // All tokens are sent to the fallback account if the transfer fails
+        set_promise_result(PromiseResult::Failed);
+        drop(contract.on_routed_transfer(token_id(), bob(), U128(10), false));
+        let calls = created_calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].2["receiver_id"], "bob.near");
+        assert_eq!(calls[0].2["amount"], "10");
+    }
+}
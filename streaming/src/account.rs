@@ -23,21 +23,120 @@ pub struct Account {
     pub is_cron_allowed: bool,
 
     pub total_streams_created: u32,
+
+    // NEP-145 storage balance in NEAR attached by the account
+    // and the storage used by streams created by the account.
+    //
+    // Accounts with zero storage balance are not registered,
+    // streams created by them are covered by finance contract.
+    pub storage_balance: Balance,
+    pub storage_usage: StorageUsage,
+
+    // Pools of tokens for streams funded from treasury, by token
+    pub treasury: HashMap<AccountId, Treasury>,
+
+    // Subscriptions the account charges as a merchant
+    // and the ones the account is charged by as a subscriber
+    pub incoming_subscriptions: UnorderedSet<SubscriptionId>,
+    pub outgoing_subscriptions: UnorderedSet<SubscriptionId>,
+
+    // Incoming streams stay pending until accepted by the account
+    pub require_accept: bool,
+
+    // Accounts approved to act on behalf of the account, by operator
+    pub operators: HashMap<AccountId, Operator>,
+
+    // Tip paid to keepers withdrawing incoming streams of the account
+    pub keeper_tip: Option<KeeperTip>,
+
+    // Destination of tokens withdrawn from incoming streams of the account
+    pub payout_route: Option<PayoutRoute>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub enum KeeperTip {
+    // Share of each payout in basis points, paid in tokens of the stream
+    Share {
+        bps: u16,
+    },
+    // Fee in NEAR per payout, paid from the deposit of the account
+    // while the deposit is enough
+    Flat {
+        #[serde(with = "u128_dec_format")]
+        amount: Balance,
+    },
+}
+
+// Account as stored by v2.2.0, before the storage balance was introduced.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct AccountV0 {
+    pub id: AccountId,
+
+    pub active_incoming_streams: UnorderedSet<StreamId>,
+    pub active_outgoing_streams: UnorderedSet<StreamId>,
+
+    pub inactive_incoming_streams: UnorderedSet<StreamId>,
+    pub inactive_outgoing_streams: UnorderedSet<StreamId>,
+
+    pub total_incoming: HashMap<AccountId, Balance>,
+    pub total_outgoing: HashMap<AccountId, Balance>,
+    pub total_received: HashMap<AccountId, Balance>,
+
+    pub deposit: Balance,
+
+    pub stake: Balance,
+
+    pub last_created_stream: Option<StreamId>,
+
+    pub is_cron_allowed: bool,
+
+    pub total_streams_created: u32,
 }
 
+// Variants are never reordered, stored accounts are migrated
+// to the current layout on read and saved as current.
+// Legacy variants only exist while being read.
+#[allow(clippy::large_enum_variant)]
 #[derive(BorshSerialize, BorshDeserialize)]
 pub enum VAccount {
+    V0(AccountV0),
     Current(Account),
 }
 
 impl From<VAccount> for Account {
     fn from(v: VAccount) -> Self {
         match v {
+            VAccount::V0(a) => a.into(),
             VAccount::Current(c) => c,
         }
     }
 }
 
+impl From<AccountV0> for Account {
+    fn from(a: AccountV0) -> Self {
+        // Sets of streams are kept under the same storage keys
+        let account = Account::new(&a.id);
+        Self {
+            id: a.id,
+            active_incoming_streams: a.active_incoming_streams,
+            active_outgoing_streams: a.active_outgoing_streams,
+            inactive_incoming_streams: a.inactive_incoming_streams,
+            inactive_outgoing_streams: a.inactive_outgoing_streams,
+            total_incoming: a.total_incoming,
+            total_outgoing: a.total_outgoing,
+            total_received: a.total_received,
+            deposit: a.deposit,
+            stake: a.stake,
+            last_created_stream: a.last_created_stream,
+            is_cron_allowed: a.is_cron_allowed,
+            total_streams_created: a.total_streams_created,
+            ..account
+        }
+    }
+}
+
 impl From<Account> for VAccount {
     fn from(c: Account) -> Self {
         VAccount::Current(c)
@@ -68,6 +167,62 @@ impl Account {
             last_created_stream: None,
             is_cron_allowed: false,
             total_streams_created: 0,
+            storage_balance: 0,
+            storage_usage: 0,
+            treasury: HashMap::new(),
+            incoming_subscriptions: UnorderedSet::new(StorageKey::IncomingSubscriptions {
+                account_id: account_id.clone(),
+            }),
+            outgoing_subscriptions: UnorderedSet::new(StorageKey::OutgoingSubscriptions {
+                account_id: account_id.clone(),
+            }),
+            require_accept: false,
+            operators: HashMap::new(),
+            keeper_tip: None,
+            payout_route: None,
+        }
+    }
+}
+
+// If the route fails, tokens are sent to the account itself.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub enum PayoutRoute {
+    Account {
+        account_id: AccountId,
+    },
+    TransferCall {
+        receiver_id: AccountId,
+        msg: String,
+    },
+    // Creates a stream owned by the account on this contract
+    Restream {
+        receiver_id: AccountId,
+        tokens_per_sec: U128,
+    },
+}
+
+impl PayoutRoute {
+    pub(crate) fn check(&self, account_id: &AccountId) -> Result<(), ContractError> {
+        match self {
+            PayoutRoute::Account {
+                account_id: destination_id,
+            }
+            | PayoutRoute::Restream {
+                receiver_id: destination_id,
+                ..
+            } if destination_id == account_id => Err(ContractError::InvalidPayoutRoute),
+            PayoutRoute::Restream { tokens_per_sec, .. }
+                if tokens_per_sec.0 == 0 || tokens_per_sec.0 > MAX_STREAMING_SPEED =>
+            {
+                Err(ContractError::InvalidStreamingSpeed {
+                    min_streaming_speed: MIN_STREAMING_SPEED,
+                    max_streaming_speed: MAX_STREAMING_SPEED,
+                    received: tokens_per_sec.0,
+                })
+            }
+            _ => Ok(()),
         }
     }
 }
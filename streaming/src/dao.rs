@@ -19,6 +19,43 @@ pub struct Dao {
     pub oracles: HashSet<AccountId>,
 
     pub approved_nfts: HashSet<AccountId>,
+
+    // Terminated streams may be pruned once the period passed
+    pub stream_prune_period_sec: u64,
+}
+
+// Dao as stored by v2.2.0, read once on upgrade.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct DaoV0 {
+    pub dao_id: AccountId,
+
+    pub tokens: HashMap<AccountId, Token>,
+    pub commission_non_payment_ft: Balance,
+
+    pub utility_token_id: AccountId,
+    pub utility_token_decimals: u8,
+
+    pub eth_near_ratio: SafeFloat,
+
+    pub oracles: HashSet<AccountId>,
+
+    pub approved_nfts: HashSet<AccountId>,
+}
+
+impl From<DaoV0> for Dao {
+    fn from(d: DaoV0) -> Self {
+        Self {
+            dao_id: d.dao_id,
+            tokens: d.tokens,
+            commission_non_payment_ft: d.commission_non_payment_ft,
+            utility_token_id: d.utility_token_id,
+            utility_token_decimals: d.utility_token_decimals,
+            eth_near_ratio: d.eth_near_ratio,
+            oracles: d.oracles,
+            approved_nfts: d.approved_nfts,
+            stream_prune_period_sec: DEFAULT_STREAM_PRUNE_PERIOD_SEC,
+        }
+    }
 }
 
 impl Dao {
@@ -36,6 +73,7 @@ impl Dao {
             eth_near_ratio: SafeFloat::ZERO,
             oracles: HashSet::new(),
             approved_nfts: HashSet::new(),
+            stream_prune_period_sec: DEFAULT_STREAM_PRUNE_PERIOD_SEC,
         }
     }
 
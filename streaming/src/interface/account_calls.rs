@@ -13,6 +13,89 @@ impl Contract {
         self.save_account(account)
     }
 
+    #[handle_result]
+    #[payable]
+    pub fn account_update_accept_flag(
+        &mut self,
+        require_accept: bool,
+    ) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        let account_id = env::predecessor_account_id();
+        self.create_account_if_not_exist(&account_id)?;
+        let mut account = self.extract_account(&account_id)?;
+        account.require_accept = require_accept;
+        self.save_account(account)
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn account_update_keeper_tip(
+        &mut self,
+        keeper_tip: Option<KeeperTip>,
+    ) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        let is_capped = match keeper_tip {
+            Some(KeeperTip::Share { bps }) => bps <= MAX_KEEPER_TIP_BPS,
+            Some(KeeperTip::Flat { amount }) => amount <= MAX_KEEPER_TIP_FLAT,
+            None => true,
+        };
+        if !is_capped {
+            return Err(ContractError::ExceededMaxKeeperTip {
+                max_bps: MAX_KEEPER_TIP_BPS,
+                max_flat: MAX_KEEPER_TIP_FLAT,
+            });
+        }
+        let mut account = self.extract_account(&env::predecessor_account_id())?;
+        account.keeper_tip = keeper_tip;
+        self.save_account(account)
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn account_update_payout_route(
+        &mut self,
+        payout_route: Option<PayoutRoute>,
+    ) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        let account_id = env::predecessor_account_id();
+        if let Some(payout_route) = &payout_route {
+            payout_route.check(&account_id)?;
+        }
+        let mut account = self.extract_account(&account_id)?;
+        account.payout_route = payout_route;
+        self.save_account(account)
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn add_operator(
+        &mut self,
+        operator_id: AccountId,
+        actions: Vec<OperatorAction>,
+        stream_id: Option<Base58CryptoHash>,
+        token_account_id: Option<AccountId>,
+        expires_at: Option<Timestamp>,
+    ) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.add_operator_op(
+            &env::predecessor_account_id(),
+            operator_id,
+            Operator {
+                actions,
+                stream_id,
+                token_account_id,
+                expires_at,
+            },
+        )
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn revoke_operator(&mut self, operator_id: AccountId) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.revoke_operator_op(&env::predecessor_account_id(), operator_id)
+    }
+
     #[handle_result]
     #[payable]
     pub fn account_deposit_near(&mut self) -> Result<(), ContractError> {
@@ -43,6 +126,21 @@ impl Contract {
             amount,
         )
     }
+
+    #[handle_result]
+    #[payable]
+    pub fn account_withdraw_treasury(
+        &mut self,
+        token_account_id: AccountId,
+        amount: Option<U128>,
+    ) -> Result<Option<Promise>, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.withdraw_treasury_op(
+            &env::predecessor_account_id(),
+            token_account_id,
+            amount.map(|a| a.into()),
+        )
+    }
 }
 
 impl Contract {
@@ -40,6 +40,130 @@ impl Contract {
         Ok(())
     }
 
+    #[handle_result]
+    #[payable]
+    pub fn dao_update_stream_prune_period(
+        &mut self,
+        stream_prune_period_sec: u64,
+    ) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.dao.check_owner()?;
+
+        self.dao.stream_prune_period_sec = stream_prune_period_sec;
+        Ok(())
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn dao_update_commission_refund_on_reject(
+        &mut self,
+        is_refunded: bool,
+    ) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.dao.check_owner()?;
+
+        self.refund_commission_on_reject = is_refunded;
+        Ok(())
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn dao_freeze_stream(&mut self, stream_id: Base58CryptoHash) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.dao.check_owner()?;
+
+        self.freeze_stream_op(stream_id.into())
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn dao_unfreeze_stream(
+        &mut self,
+        stream_id: Base58CryptoHash,
+    ) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.dao.check_owner()?;
+
+        self.unfreeze_stream_op(stream_id.into())
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn dao_clawback_stream(
+        &mut self,
+        stream_id: Base58CryptoHash,
+        is_unwithdrawn_returned: Option<bool>,
+    ) -> Result<Vec<Promise>, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.dao.check_owner()?;
+
+        self.clawback_stream_op(stream_id.into(), is_unwithdrawn_returned.unwrap_or(false))
+    }
+
+    // Indexes streams by tokens, needed once for streams
+    // created before the token index was introduced.
+    #[handle_result]
+    #[payable]
+    pub fn dao_index_token_streams(&mut self, from: u32, limit: u32) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.dao.check_owner()?;
+
+        for i in from..min(self.streams.len() as _, from.saturating_add(limit)) {
+            let stream: Stream = self.streams.values_as_vector().get(i as _).unwrap().into();
+            self.index_token_stream(&stream);
+        }
+        Ok(())
+    }
+
+    // Rewrites streams stored before the upgrade in the current layout,
+    // they're counted by status after that.
+    #[handle_result]
+    #[payable]
+    pub fn dao_migrate_streams(&mut self, from: u32, limit: u32) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.dao.check_owner()?;
+
+        self.migrate_streams_op(from, limit);
+        Ok(())
+    }
+
+    // Sums balances of the token streams page by page,
+    // then compares them with TVL and the balance of finance contract.
+    #[handle_result]
+    #[payable]
+    pub fn dao_reconcile_tvl(
+        &mut self,
+        token_account_id: AccountId,
+        limit: Option<u32>,
+    ) -> Result<PromiseOrValue<TvlReconciliation>, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.dao.check_owner()?;
+
+        self.reconcile_tvl_op(token_account_id, limit.unwrap_or(MAX_VIEW_STREAMS_SCAN))
+    }
+
+    #[private]
+    pub fn on_reconcile_tvl(
+        &mut self,
+        #[callback_result] finance_balance: Result<U128, PromiseError>,
+        reconciliation: TvlReconciliation,
+    ) -> TvlReconciliation {
+        let reconciliation = TvlReconciliation {
+            finance_balance: finance_balance.ok(),
+            ..reconciliation
+        };
+        let expected_balance = reconciliation.streams_balance + reconciliation.treasury_balance;
+        log!(
+            "TVL reconciliation of {}: tvl - streams balance = {}, finance balance - streams and treasury balance = {}",
+            reconciliation.token_account_id,
+            reconciliation.tvl as i128 - reconciliation.streams_balance as i128,
+            reconciliation.finance_balance.map_or("unknown".to_string(), |finance_balance| {
+                (finance_balance.0 as i128 - expected_balance as i128).to_string()
+            }),
+        );
+        reconciliation
+    }
+
     #[handle_result]
     #[payable]
     pub fn dao_withdraw_ft(
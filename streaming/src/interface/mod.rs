@@ -1,6 +1,8 @@
 mod account_calls;
 mod dao_calls;
+mod nft_calls;
 mod oracle_calls;
 mod stream_calls;
+mod subscription_calls;
 pub mod token_calls;
 pub mod views;
@@ -0,0 +1,123 @@
+use crate::*;
+
+// NEP-171 core and NEP-178 approval management of stream NFTs,
+// views are in `views.rs`.
+#[near_bindgen]
+impl Contract {
+    #[handle_result]
+    #[payable]
+    pub fn mint_stream_nft(
+        &mut self,
+        stream_id: Base58CryptoHash,
+    ) -> Result<TokenId, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.mint_stream_nft_op(&env::predecessor_account_id(), stream_id.into())
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) -> Result<Vec<Promise>, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.nft_transfer_op(
+            &env::predecessor_account_id(),
+            receiver_id,
+            &token_id,
+            approval_id,
+            memo,
+        )
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> Result<PromiseOrValue<bool>, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        check_gas(GAS_FOR_NFT_ON_TRANSFER + GAS_FOR_NFT_RESOLVE_TRANSFER)?;
+
+        let sender_id = env::predecessor_account_id();
+        let (stream, _) = self.view_stream_nft(&token_id)?;
+        let previous_owner_id = stream.receiver_id;
+        self.nft_transfer_op(
+            &sender_id,
+            receiver_id.clone(),
+            &token_id,
+            approval_id,
+            memo,
+        )?;
+
+        let promise = ext_nft_receiver::ext(receiver_id.clone())
+            .with_static_gas(GAS_FOR_NFT_ON_TRANSFER)
+            .nft_on_transfer(sender_id, previous_owner_id.clone(), token_id.clone(), msg)
+            .then(
+                ext_nft_resolver::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_NFT_RESOLVE_TRANSFER)
+                    .nft_resolve_transfer(previous_owner_id, receiver_id, token_id),
+            );
+        Ok(PromiseOrValue::Promise(promise))
+    }
+
+    // The token stays with the receiver if it can't be transferred back.
+    #[private]
+    #[handle_result]
+    pub fn nft_resolve_transfer(
+        &mut self,
+        #[callback_result] is_returned: Result<bool, PromiseError>,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+    ) -> Result<bool, ContractError> {
+        if let Ok(false) = is_returned {
+            return Ok(true);
+        }
+        self.nft_resolve_transfer_op(previous_owner_id, receiver_id, &token_id)
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn nft_approve(
+        &mut self,
+        token_id: TokenId,
+        account_id: AccountId,
+        msg: Option<String>,
+    ) -> Result<Option<Promise>, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        let owner_id = env::predecessor_account_id();
+        let approval_id = self.nft_approve_op(&owner_id, &token_id, account_id.clone())?;
+
+        Ok(msg.map(|msg| {
+            ext_nft_approval_receiver::ext(account_id)
+                .with_static_gas(GAS_FOR_NFT_ON_APPROVE)
+                .nft_on_approve(token_id, owner_id, approval_id, msg)
+        }))
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn nft_revoke(
+        &mut self,
+        token_id: TokenId,
+        account_id: AccountId,
+    ) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.nft_revoke_op(&env::predecessor_account_id(), &token_id, Some(account_id))
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn nft_revoke_all(&mut self, token_id: TokenId) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.nft_revoke_op(&env::predecessor_account_id(), &token_id, None)
+    }
+}
@@ -46,6 +46,192 @@ impl Contract {
         self.stop_stream_op(&env::predecessor_account_id(), stream_id.into())
     }
 
+    #[handle_result]
+    #[payable]
+    pub fn approve_milestone(&mut self, stream_id: Base58CryptoHash) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.approve_milestone_op(&env::predecessor_account_id(), stream_id.into())
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn reject_milestone(
+        &mut self,
+        stream_id: Base58CryptoHash,
+    ) -> Result<Vec<Promise>, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.reject_milestone_op(&env::predecessor_account_id(), stream_id.into())
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn split_stream(
+        &mut self,
+        stream_id: Base58CryptoHash,
+        ratio_bps: u16,
+    ) -> Result<Base58CryptoHash, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.split_stream_op(&env::predecessor_account_id(), stream_id.into(), ratio_bps)
+            .map(Base58CryptoHash::from)
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn merge_streams(
+        &mut self,
+        stream_ids: Vec<Base58CryptoHash>,
+    ) -> Result<Vec<Promise>, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.merge_streams_op(
+            &env::predecessor_account_id(),
+            stream_ids.into_iter().map(CryptoHash::from).collect(),
+        )
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn open_dispute(&mut self, stream_id: Base58CryptoHash) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.open_dispute_op(&env::predecessor_account_id(), stream_id.into())
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn resolve_dispute(
+        &mut self,
+        stream_id: Base58CryptoHash,
+        receiver_amount: U128,
+    ) -> Result<Vec<Promise>, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.resolve_dispute_op(
+            &env::predecessor_account_id(),
+            stream_id.into(),
+            receiver_amount.into(),
+        )
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn pledge_stream(
+        &mut self,
+        stream_id: Base58CryptoHash,
+        lender_id: AccountId,
+        amount: U128,
+        share_bps: u16,
+    ) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.pledge_stream_op(
+            &env::predecessor_account_id(),
+            stream_id.into(),
+            lender_id,
+            amount.into(),
+            share_bps,
+        )
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn release_pledge(&mut self, stream_id: Base58CryptoHash) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.release_pledge_op(&env::predecessor_account_id(), stream_id.into())
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn accept_stream(&mut self, stream_id: Base58CryptoHash) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.accept_stream_op(&env::predecessor_account_id(), stream_id.into())
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn reject_stream(
+        &mut self,
+        stream_id: Base58CryptoHash,
+    ) -> Result<Vec<Promise>, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.reject_stream_op(&env::predecessor_account_id(), stream_id.into())
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn chain_stream(
+        &mut self,
+        stream_id: Base58CryptoHash,
+        child_stream_id: Option<Base58CryptoHash>,
+    ) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.chain_stream_op(
+            &env::predecessor_account_id(),
+            stream_id.into(),
+            child_stream_id.map(|child_stream_id| child_stream_id.into()),
+        )
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn start_streams(
+        &mut self,
+        stream_ids: Vec<Base58CryptoHash>,
+    ) -> Result<BatchResult, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+
+        self.batch_op(
+            stream_ids
+                .into_iter()
+                .map(|stream_id| stream_id.into())
+                .collect(),
+            |_, _| GAS_FOR_BATCH_START,
+            |contract, stream_id| {
+                contract.start_stream_op(&env::predecessor_account_id(), stream_id)
+            },
+        )
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn pause_streams(
+        &mut self,
+        stream_ids: Vec<Base58CryptoHash>,
+    ) -> Result<BatchResult, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+
+        self.batch_op(
+            stream_ids
+                .into_iter()
+                .map(|stream_id| stream_id.into())
+                .collect(),
+            |contract, stream_id| contract.gas_for_batch(stream_id, false),
+            |contract, stream_id| {
+                contract
+                    .pause_stream_op(&env::predecessor_account_id(), stream_id)
+                    .map(|_| ())
+            },
+        )
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn stop_streams(
+        &mut self,
+        stream_ids: Vec<Base58CryptoHash>,
+    ) -> Result<BatchResult, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+
+        self.batch_op(
+            stream_ids
+                .into_iter()
+                .map(|stream_id| stream_id.into())
+                .collect(),
+            |contract, stream_id| contract.gas_for_batch(stream_id, true),
+            |contract, stream_id| {
+                contract
+                    .stop_stream_op(&env::predecessor_account_id(), stream_id)
+                    .map(|_| ())
+            },
+        )
+    }
+
     #[handle_result]
     #[payable]
     pub fn withdraw(
@@ -63,6 +249,24 @@ impl Contract {
             .collect())
     }
 
+    #[handle_result]
+    #[payable]
+    pub fn prune_streams(
+        &mut self,
+        stream_ids: Vec<Base58CryptoHash>,
+        is_archived: Option<bool>,
+    ) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+
+        stream_ids.into_iter().try_for_each(|stream_id| {
+            self.prune_stream_op(
+                &env::predecessor_account_id(),
+                stream_id.into(),
+                is_archived.unwrap_or(true),
+            )
+        })
+    }
+
     #[handle_result]
     #[payable]
     pub fn nft_change_receiver(
@@ -104,6 +308,7 @@ impl Contract {
             stream_id.into(),
             receiver_id,
             deposit_needed,
+            env::attached_deposit(),
         )
     }
 }
@@ -0,0 +1,49 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    #[handle_result]
+    #[payable]
+    pub fn create_subscription(
+        &mut self,
+        merchant_id: AccountId,
+        token_account_id: AccountId,
+        amount: U128,
+        period_sec: u32,
+    ) -> Result<Base58CryptoHash, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.create_subscription_op(
+            &env::predecessor_account_id(),
+            merchant_id,
+            token_account_id,
+            amount.into(),
+            period_sec,
+        )
+        .map(|subscription_id| subscription_id.into())
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn charge_subscription(
+        &mut self,
+        subscription_id: Base58CryptoHash,
+        amount: Option<U128>,
+    ) -> Result<Option<Promise>, ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.charge_subscription_op(
+            &env::predecessor_account_id(),
+            subscription_id.into(),
+            amount.map(|a| a.into()),
+        )
+    }
+
+    #[handle_result]
+    #[payable]
+    pub fn cancel_subscription(
+        &mut self,
+        subscription_id: Base58CryptoHash,
+    ) -> Result<(), ContractError> {
+        check_deposit(ONE_YOCTO)?;
+        self.cancel_subscription_op(&env::predecessor_account_id(), subscription_id.into())
+    }
+}
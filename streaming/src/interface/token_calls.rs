@@ -4,8 +4,10 @@ use crate::*;
 #[serde(crate = "near_sdk::serde")]
 pub enum TransferCallRequest {
     Stake,
-    Create { request: CreateRequest },
+    Create { request: Box<CreateRequest> },
+    CreateBatch { requests: Vec<CreateRequest> },
     Deposit { stream_id: Base58CryptoHash },
+    FundTreasury,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -19,6 +21,19 @@ pub struct CreateRequest {
     pub is_auto_start_enabled: Option<bool>,
     pub is_expirable: Option<bool>,
     pub is_locked: Option<bool>,
+    // Required for batches only, single streams take the whole amount
+    pub balance: Option<U128>,
+    // Draw tokens from the owner's treasury, the balance is deposited there
+    pub funded_from_treasury: Option<bool>,
+    // Withdrawals are allowed up to the last milestone approved
+    pub approver_id: Option<AccountId>,
+    pub milestones: Option<Vec<U128>>,
+    // Restrictions on stopping the stream by the owner
+    pub cancel_policy: Option<CancelPolicy>,
+    // Allow the DAO to freeze and claw back the stream
+    pub is_clawback_allowed: Option<bool>,
+    // Resolves disputes opened by the owner or the receiver
+    pub arbiter_id: Option<AccountId>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -109,23 +124,26 @@ impl FungibleTokenReceiver for Contract {
                 PromiseOrValue::Value(U128::from(0))
             }
             TransferCallRequest::Create { request } => {
-                match self.create_stream_op(
-                    request.description,
-                    sender_id,
-                    request.owner_id,
-                    request.receiver_id,
-                    token_account_id,
-                    amount.into(),
-                    request.tokens_per_sec.into(),
-                    request.cliff_period_sec,
-                    request.is_auto_start_enabled,
-                    request.is_expirable,
-                    request.is_locked,
-                ) {
+                match self.create_stream_op(sender_id, token_account_id, amount.into(), *request) {
                     Ok(()) => PromiseOrValue::Value(U128::from(0)),
                     Err(err) => panic!("error on stream creation, {:?}", err),
                 }
             }
+            TransferCallRequest::CreateBatch { requests } => {
+                match self.create_streams_op(sender_id, token_account_id, amount.into(), requests) {
+                    Ok(stream_ids) => {
+                        log!(
+                            "created streams {:?}",
+                            stream_ids
+                                .into_iter()
+                                .map(Base58CryptoHash::from)
+                                .collect::<Vec<_>>()
+                        );
+                        PromiseOrValue::Value(U128::from(0))
+                    }
+                    Err(err) => panic!("error on batch stream creation, {:?}", err),
+                }
+            }
             TransferCallRequest::Deposit { stream_id } => {
                 match self.deposit_op(token_account_id, stream_id.into(), amount.into()) {
                     Ok(()) => PromiseOrValue::Value(U128::from(0)),
@@ -137,6 +155,12 @@ impl FungibleTokenReceiver for Contract {
                     Err(err) => panic!("error on stream depositing, {:?}", err),
                 }
             }
+            TransferCallRequest::FundTreasury => {
+                match self.fund_treasury_op(&sender_id, token_account_id, amount.into()) {
+                    Ok(()) => PromiseOrValue::Value(U128::from(0)),
+                    Err(err) => panic!("error on treasury funding, {:?}", err),
+                }
+            }
         }
     }
 }
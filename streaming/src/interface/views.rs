@@ -21,6 +21,118 @@ pub struct AccountView {
 
     pub last_created_stream: Option<Base58CryptoHash>,
     pub is_cron_allowed: bool,
+    pub require_accept: bool,
+    pub keeper_tip: Option<KeeperTip>,
+    pub payout_route: Option<PayoutRoute>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub enum StreamDirection {
+    Incoming,
+    Outgoing,
+}
+
+// Terminated streams are not indexed by accounts,
+// so only statuses of non-terminated streams are here.
+#[derive(Deserialize, Serialize, PartialEq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub enum StreamStatusFilter {
+    Pending,
+    Initialized,
+    Active,
+    Paused,
+    Disputed,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct StreamFilter {
+    pub status: Option<StreamStatusFilter>,
+    pub token_account_id: Option<AccountId>,
+    // For account views it's the other side of the stream,
+    // otherwise it's either owner or receiver.
+    pub counterparty_id: Option<AccountId>,
+    pub created_from: Option<Timestamp>,
+    pub created_to: Option<Timestamp>,
+}
+
+impl StreamFilter {
+    fn matches(&self, stream: &Stream, counterparty_ids: &[&AccountId]) -> bool {
+        let status_matches = match self.status {
+            None => true,
+            Some(StreamStatusFilter::Pending) => {
+                matches!(stream.status, StreamStatus::Pending { .. })
+            }
+            Some(StreamStatusFilter::Initialized) => stream.status == StreamStatus::Initialized,
+            Some(StreamStatusFilter::Active) => stream.status == StreamStatus::Active,
+            Some(StreamStatusFilter::Paused) => stream.status == StreamStatus::Paused,
+            Some(StreamStatusFilter::Disputed) => {
+                matches!(stream.status, StreamStatus::Disputed { .. })
+            }
+        };
+        status_matches
+            && self
+                .token_account_id
+                .as_ref()
+                .is_none_or(|token_account_id| stream.token_account_id == *token_account_id)
+            && self
+                .counterparty_id
+                .as_ref()
+                .is_none_or(|counterparty_id| counterparty_ids.contains(&counterparty_id))
+            && self
+                .created_from
+                .is_none_or(|timestamp| stream.timestamp_created >= timestamp)
+            && self
+                .created_to
+                .is_none_or(|timestamp| stream.timestamp_created < timestamp)
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct StreamsPage {
+    pub streams: Vec<Stream>,
+    // Pass it as a cursor to get the next page, none if nothing left.
+    pub next_cursor: Option<u32>,
+
+    pub active_streams: u32,
+    // Terminated streams are not counted
+    pub inactive_streams: u32,
+    // Counts of all streams by status, only for filtered streams,
+    // as counting streams of an account or a token needs all of them read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_counts: Option<StreamStatusCounts>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct StreamForecast {
+    #[serde(with = "b58_dec_format")]
+    pub stream_id: CryptoHash,
+    pub run_out_at: Timestamp,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenForecast {
+    // Available to withdraw from incoming streams at the moment
+    #[serde(with = "u128_dec_format")]
+    pub receivable: Balance,
+    // Not streamed yet by outgoing streams at the moment,
+    // including severance of scheduled stops
+    #[serde(with = "u128_dec_format")]
+    pub outgoing_remaining: Balance,
+
+    pub outgoing_streams: Vec<StreamForecast>,
+    // The earliest run out time of outgoing streams
+    pub runs_dry_at: Option<Timestamp>,
 }
 
 #[near_bindgen]
@@ -30,13 +142,37 @@ impl Contract {
         stats.total_dao_tokens = stats.dao_tokens.len() as _;
         stats.total_accounts = self.accounts.len() as _;
         stats.total_streams = self.streams.len() as _;
+        stats.total_archived_streams = self.archived_streams.len() as _;
         stats
     }
 
+    // Daily stats of the token for days in [from_day, to_day],
+    // days with no activity are skipped.
+    pub fn get_stats_history(
+        &self,
+        token_account_id: AccountId,
+        from_day: u32,
+        to_day: u32,
+    ) -> Vec<StatsBucket> {
+        // Older buckets are overwritten anyway
+        let from_day = max(from_day, to_day.saturating_sub(STATS_HISTORY_DAYS - 1));
+        (from_day..=to_day)
+            .filter_map(|day| {
+                self.stats_history
+                    .get(&(token_account_id.clone(), day % STATS_HISTORY_DAYS))
+                    .filter(|bucket| bucket.day == day)
+            })
+            .collect()
+    }
+
     pub fn get_dao(self) -> Dao {
         self.dao
     }
 
+    pub fn get_commission_refund_on_reject(self) -> bool {
+        self.refund_commission_on_reject
+    }
+
     pub fn get_token(self, token_account_id: AccountId) -> (Token, Option<TokenStats>) {
         (
             self.dao.get_token(&token_account_id),
@@ -48,7 +184,87 @@ impl Contract {
 
     #[handle_result]
     pub fn get_stream(self, stream_id: Base58CryptoHash) -> Result<Stream, ContractError> {
-        self.view_stream(&stream_id.into())
+        let mut stream = self.view_stream(&stream_id.into())?;
+        if stream.funded_from_treasury {
+            stream.available_to_withdraw_by_formula = self.treasury_available_to_withdraw(&stream);
+        }
+        Ok(stream)
+    }
+
+    // Streams chained with the stream, from the topmost parent
+    // down to the last child. Streams pruned already are skipped.
+    #[handle_result]
+    pub fn get_stream_chain(
+        self,
+        stream_id: Base58CryptoHash,
+    ) -> Result<Vec<Stream>, ContractError> {
+        let stream = self.view_stream(&stream_id.into())?;
+        let mut visited = HashSet::from([stream.id]);
+        let mut parents = vec![];
+        let mut parent_stream_id = stream.parent_stream_id;
+        while let Some(stream) = parent_stream_id
+            .and_then(|stream_id| self.view_stream(&stream_id.into()).ok())
+            .filter(|stream| visited.insert(stream.id))
+        {
+            parent_stream_id = stream.parent_stream_id;
+            parents.push(stream);
+        }
+        parents.reverse();
+
+        let mut chain = parents;
+        let mut child_stream_id = stream.child_stream_id;
+        chain.push(stream);
+        while let Some(stream) = child_stream_id
+            .and_then(|stream_id| self.view_stream(&stream_id.into()).ok())
+            .filter(|stream| visited.insert(stream.id))
+        {
+            child_stream_id = stream.child_stream_id;
+            chain.push(stream);
+        }
+        Ok(chain)
+    }
+
+    #[handle_result]
+    pub fn get_archived_stream(
+        self,
+        stream_id: Base58CryptoHash,
+    ) -> Result<ArchivedStream, ContractError> {
+        let stream_id = stream_id.into();
+        self.archived_streams
+            .get(&stream_id)
+            .ok_or(ContractError::StreamNotExist { stream_id })
+    }
+
+    #[handle_result]
+    pub fn get_subscription(
+        self,
+        subscription_id: Base58CryptoHash,
+    ) -> Result<Subscription, ContractError> {
+        self.view_subscription(&subscription_id.into())
+    }
+
+    // Subscriptions the account is charged by.
+    #[handle_result]
+    pub fn get_subscriber_subscriptions(
+        self,
+        account_id: AccountId,
+        from: Option<u32>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Subscription>, ContractError> {
+        let account = self.view_account(&account_id, false)?;
+        Ok(self.collect_subscriptions(&account.outgoing_subscriptions, from, limit))
+    }
+
+    // Subscriptions the account charges.
+    #[handle_result]
+    pub fn get_merchant_subscriptions(
+        self,
+        account_id: AccountId,
+        from: Option<u32>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Subscription>, ContractError> {
+        let account = self.view_account(&account_id, false)?;
+        Ok(self.collect_subscriptions(&account.incoming_subscriptions, from, limit))
     }
 
     #[handle_result]
@@ -87,9 +303,97 @@ impl Contract {
                 stake: a.stake,
                 last_created_stream: a.last_created_stream.map(|w| w.into()),
                 is_cron_allowed: a.is_cron_allowed,
+                require_accept: a.require_accept,
+                keeper_tip: a.keeper_tip,
+                payout_route: a.payout_route,
             })
     }
 
+    #[handle_result]
+    pub fn get_account_operators(
+        self,
+        account_id: AccountId,
+    ) -> Result<HashMap<AccountId, Operator>, ContractError> {
+        self.view_account(&account_id, false)
+            .map(|account| account.operators)
+    }
+
+    // Treasury of the account drained up to the current moment.
+    #[handle_result]
+    pub fn get_account_treasury(
+        self,
+        account_id: AccountId,
+        token_account_id: AccountId,
+    ) -> Result<Treasury, ContractError> {
+        self.view_account(&account_id, false)
+            .map(|account| account.view_treasury(&token_account_id))
+    }
+
+    // Projects active streams of the account to the moment `at`,
+    // assuming no actions are made with the streams until then.
+    #[handle_result]
+    pub fn get_account_forecast(
+        self,
+        account_id: AccountId,
+        at: Timestamp,
+    ) -> Result<HashMap<AccountId, TokenForecast>, ContractError> {
+        let at = max(at, env::block_timestamp());
+        let account = self.view_account(&account_id, false)?;
+        let mut forecast: HashMap<AccountId, TokenForecast> = HashMap::new();
+
+        for stream_id in account.active_incoming_streams.iter() {
+            let stream = self.view_stream(&stream_id)?;
+            let receivable = if stream.funded_from_treasury {
+                self.treasury_available_to_withdraw_at(&stream, at)
+            } else {
+                stream.available_to_withdraw_at(at)
+            };
+            forecast
+                .entry(stream.token_account_id.clone())
+                .or_default()
+                .receivable += receivable;
+        }
+
+        // Streams funded from treasury have no balance,
+        // the treasury shared by them runs out at once.
+        let mut treasury_token_ids = HashSet::new();
+        for stream_id in account.active_outgoing_streams.iter() {
+            let stream = self.view_stream(&stream_id)?;
+            let run_out_at = if stream.funded_from_treasury {
+                treasury_token_ids.insert(stream.token_account_id.clone());
+                account
+                    .view_treasury(&stream.token_account_id)
+                    .run_out_time()
+            } else {
+                // Nothing is streamed after the scheduled stop
+                stream.stop_at.map_or(stream.run_out_time(), |stop_at| {
+                    min(stream.run_out_time(), stop_at)
+                })
+            };
+            let token_forecast = forecast.entry(stream.token_account_id.clone()).or_default();
+            if !stream.funded_from_treasury {
+                token_forecast.outgoing_remaining += stream.outgoing_remaining_at(at);
+            }
+            token_forecast.outgoing_streams.push(StreamForecast {
+                stream_id,
+                run_out_at,
+            });
+            token_forecast.runs_dry_at = Some(
+                token_forecast
+                    .runs_dry_at
+                    .map_or(run_out_at, |runs_dry_at| min(runs_dry_at, run_out_at)),
+            );
+        }
+        for token_account_id in treasury_token_ids {
+            forecast
+                .get_mut(&token_account_id)
+                .unwrap()
+                .outgoing_remaining += account.view_treasury_at(&token_account_id, at).balance;
+        }
+
+        Ok(forecast)
+    }
+
     #[handle_result]
     pub fn get_account_incoming_streams(
         self,
@@ -139,6 +443,189 @@ impl Contract {
             .collect())
     }
 
+    // Active streams with at least `min_amount` tokens available
+    // to withdraw by keepers, among streams in [from, from + limit).
+    pub fn get_withdrawable_streams(
+        &self,
+        min_amount: U128,
+        from: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<Stream> {
+        let from = from.unwrap_or(0);
+        let limit = limit.unwrap_or(DEFAULT_VIEW_STREAMS_LIMIT);
+        (from..min(self.streams.len() as _, from.saturating_add(limit)))
+            .map(|i| -> Stream { self.streams.values_as_vector().get(i as _).unwrap().into() })
+            .filter(|stream| {
+                stream.status == StreamStatus::Active
+                    && stream.frozen_at.is_none()
+                    && stream
+                        .cliff
+                        .is_none_or(|cliff| cliff <= env::block_timestamp())
+            })
+            .map(|mut stream| {
+                if stream.funded_from_treasury {
+                    stream.available_to_withdraw_by_formula =
+                        self.treasury_available_to_withdraw(&stream);
+                }
+                stream
+            })
+            .filter(|stream| {
+                stream.available_to_withdraw_by_formula >= min_amount.0
+                    && self
+                        .view_account(&stream.receiver_id, true)
+                        .is_ok_and(|receiver| receiver.is_cron_allowed)
+            })
+            .collect()
+    }
+
+    #[handle_result]
+    pub fn get_account_streams(
+        self,
+        account_id: AccountId,
+        direction: StreamDirection,
+        filter: Option<StreamFilter>,
+        cursor: Option<u32>,
+        limit: Option<u32>,
+    ) -> Result<StreamsPage, ContractError> {
+        let filter = filter.unwrap_or_default();
+        let limit = limit.unwrap_or(DEFAULT_VIEW_STREAMS_LIMIT);
+        let account = self.view_account(&account_id, false)?;
+        let (active_streams, inactive_streams) = match direction {
+            StreamDirection::Incoming => (
+                &account.active_incoming_streams,
+                &account.inactive_incoming_streams,
+            ),
+            StreamDirection::Outgoing => (
+                &account.active_outgoing_streams,
+                &account.inactive_outgoing_streams,
+            ),
+        };
+
+        // Cursor points to the active streams first, then to the inactive ones.
+        // Statuses are split between the indexes, so only needed part is scanned.
+        let active_len = active_streams.len() as u32;
+        let total_len = active_len + inactive_streams.len() as u32;
+        let (begin, end) = match filter.status {
+            None => (0, total_len),
+            Some(StreamStatusFilter::Active) => (0, active_len),
+            Some(_) => (active_len, total_len),
+        };
+
+        let (streams, next_cursor) = self.collect_streams_page(
+            max(begin, cursor.unwrap_or(0)),
+            end,
+            limit,
+            |i| self.get_indexed_stream(active_streams, inactive_streams, i),
+            |stream| {
+                let counterparty_id = match direction {
+                    StreamDirection::Incoming => &stream.owner_id,
+                    StreamDirection::Outgoing => &stream.receiver_id,
+                };
+                filter.matches(stream, &[counterparty_id])
+            },
+        );
+
+        Ok(StreamsPage {
+            streams,
+            next_cursor,
+            active_streams: active_len,
+            inactive_streams: inactive_streams.len() as _,
+            status_counts: None,
+        })
+    }
+
+    pub fn get_filtered_streams(
+        &self,
+        filter: Option<StreamFilter>,
+        cursor: Option<u32>,
+        limit: Option<u32>,
+    ) -> StreamsPage {
+        let filter = filter.unwrap_or_default();
+        let limit = limit.unwrap_or(DEFAULT_VIEW_STREAMS_LIMIT);
+
+        let (streams, next_cursor) = self.collect_streams_page(
+            cursor.unwrap_or(0),
+            self.streams.len() as _,
+            limit,
+            |i| self.streams.values_as_vector().get(i as _).unwrap().into(),
+            |stream| filter.matches(stream, &[&stream.owner_id, &stream.receiver_id]),
+        );
+
+        StreamsPage {
+            streams,
+            next_cursor,
+            active_streams: self.stream_status_counts.active,
+            inactive_streams: self.stream_status_counts.inactive(),
+            status_counts: Some(self.stream_status_counts.clone()),
+        }
+    }
+
+    pub fn get_token_streams(
+        &self,
+        token_account_id: AccountId,
+        status: Option<StreamStatusFilter>,
+        cursor: Option<u32>,
+        limit: Option<u32>,
+    ) -> StreamsPage {
+        let limit = limit.unwrap_or(DEFAULT_VIEW_STREAMS_LIMIT);
+        let token_streams = match self.token_streams.get(&token_account_id) {
+            Some(token_streams) => token_streams,
+            None => {
+                return StreamsPage {
+                    streams: vec![],
+                    next_cursor: None,
+                    active_streams: 0,
+                    inactive_streams: 0,
+                    status_counts: None,
+                }
+            }
+        };
+        let filter = StreamFilter {
+            status,
+            ..Default::default()
+        };
+
+        // Same cursor semantics as for account streams.
+        let active_len = token_streams.active_streams.len() as u32;
+        let total_len = active_len + token_streams.inactive_streams.len() as u32;
+        let (begin, end) = match filter.status {
+            None => (0, total_len),
+            Some(StreamStatusFilter::Active) => (0, active_len),
+            Some(_) => (active_len, total_len),
+        };
+
+        let (streams, next_cursor) = self.collect_streams_page(
+            max(begin, cursor.unwrap_or(0)),
+            end,
+            limit,
+            |i| {
+                self.get_indexed_stream(
+                    &token_streams.active_streams,
+                    &token_streams.inactive_streams,
+                    i,
+                )
+            },
+            |stream| filter.matches(stream, &[]),
+        );
+
+        StreamsPage {
+            streams,
+            next_cursor,
+            active_streams: active_len,
+            inactive_streams: token_streams.inactive_streams.len() as _,
+            status_counts: None,
+        }
+    }
+
+    // Total tokens per second streamed by all active streams of the token
+    pub fn get_token_outflow(&self, token_account_id: AccountId) -> U128 {
+        U128(
+            self.token_streams
+                .get(&token_account_id)
+                .map_or(0, |token_streams| token_streams.outflow),
+        )
+    }
+
     #[handle_result]
     pub fn get_account_ft(
         self,
@@ -152,6 +639,89 @@ impl Contract {
             (*account.total_received.get(&token_account_id).unwrap_or(&0)).into(),
         ))
     }
+
+    // NEP-171, NEP-177, NEP-178 and NEP-181 views of stream NFTs
+
+    pub fn nft_token(self, token_id: TokenId) -> Option<NftToken> {
+        let (stream, stream_nft) = self.view_stream_nft(&token_id).ok()?;
+        Some(self.stream_nft_token(&stream, stream_nft))
+    }
+
+    pub fn nft_is_approved(
+        self,
+        token_id: TokenId,
+        approved_account_id: AccountId,
+        approval_id: Option<u64>,
+    ) -> bool {
+        self.view_stream_nft(&token_id)
+            .ok()
+            .and_then(|(_, stream_nft)| {
+                stream_nft
+                    .approved_account_ids
+                    .get(&approved_account_id)
+                    .copied()
+            })
+            .is_some_and(|id| approval_id.is_none_or(|approval_id| approval_id == id))
+    }
+
+    pub fn nft_metadata(self) -> NFTContractMetadata {
+        NFTContractMetadata {
+            spec: NFT_METADATA_SPEC.to_string(),
+            name: "Roketo streams".to_string(),
+            symbol: "STREAM".to_string(),
+            icon: None,
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+
+    pub fn nft_total_supply(self) -> U128 {
+        U128(self.stream_nfts.len() as _)
+    }
+
+    pub fn nft_tokens(self, from_index: Option<U128>, limit: Option<u64>) -> Vec<NftToken> {
+        let from = from_index.map_or(0, |from_index| from_index.0 as u64);
+        let limit = limit.unwrap_or(DEFAULT_VIEW_STREAMS_LIMIT as _);
+        let stream_ids = self.stream_nfts.keys_as_vector();
+        (from..min(stream_ids.len(), from.saturating_add(limit)))
+            .map(|i| {
+                let stream_id = stream_ids.get(i).unwrap();
+                let stream = self.streams.get(&stream_id).unwrap().into();
+                self.stream_nft_token(&stream, self.stream_nfts.get(&stream_id).unwrap())
+            })
+            .collect()
+    }
+
+    pub fn nft_supply_for_owner(self, account_id: AccountId) -> U128 {
+        U128(
+            self.owner_stream_nfts
+                .get(&account_id)
+                .map_or(0, |stream_ids| stream_ids.len()) as _,
+        )
+    }
+
+    pub fn nft_tokens_for_owner(
+        self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<NftToken> {
+        let stream_ids = match self.owner_stream_nfts.get(&account_id) {
+            Some(stream_ids) => stream_ids,
+            None => return vec![],
+        };
+        let stream_ids = stream_ids.as_vector();
+        let from = from_index.map_or(0, |from_index| from_index.0 as u64);
+        let limit = limit.unwrap_or(DEFAULT_VIEW_STREAMS_LIMIT as _);
+        (from..min(stream_ids.len(), from.saturating_add(limit)))
+            .map(|i| {
+                let stream_id = stream_ids.get(i).unwrap();
+                let stream = self.streams.get(&stream_id).unwrap().into();
+                self.stream_nft_token(&stream, self.stream_nfts.get(&stream_id).unwrap())
+            })
+            .collect()
+    }
 }
 
 impl Contract {
@@ -162,21 +732,64 @@ impl Contract {
         from: u32,
         limit: u32,
     ) -> Vec<Stream> {
-        (from..min(active_streams.len() as _, from + limit))
+        // Active and inactive streams are paged as a single list
+        let total_len = (active_streams.len() + inactive_streams.len()) as u32;
+        (from..min(total_len, from + limit))
+            .map(|i| self.get_indexed_stream(active_streams, inactive_streams, i))
+            .collect()
+    }
+
+    fn collect_subscriptions(
+        &self,
+        subscriptions: &UnorderedSet<SubscriptionId>,
+        from: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<Subscription> {
+        let from = from.unwrap_or(0);
+        let limit = limit.unwrap_or(DEFAULT_VIEW_STREAMS_LIMIT);
+        (from..min(subscriptions.len() as u32, from.saturating_add(limit)))
             .map(|i| {
-                self.streams
-                    .get(&active_streams.as_vector().get(i as _).unwrap())
-                    .unwrap()
-                    .into()
+                let subscription_id = subscriptions.as_vector().get(i as _).unwrap();
+                self.subscriptions.get(&subscription_id).unwrap().into()
             })
-            .chain(
-                (from..min(inactive_streams.len() as _, from + limit)).map(|i| {
-                    self.streams
-                        .get(&inactive_streams.as_vector().get(i as _).unwrap())
-                        .unwrap()
-                        .into()
-                }),
-            )
             .collect()
     }
+
+    pub(crate) fn get_indexed_stream(
+        &self,
+        active_streams: &UnorderedSet<StreamId>,
+        inactive_streams: &UnorderedSet<StreamId>,
+        index: u32,
+    ) -> Stream {
+        let active_len = active_streams.len() as u32;
+        let stream_id = if index < active_len {
+            active_streams.as_vector().get(index as _)
+        } else {
+            inactive_streams.as_vector().get((index - active_len) as _)
+        };
+        self.streams.get(&stream_id.unwrap()).unwrap().into()
+    }
+
+    fn collect_streams_page(
+        &self,
+        cursor: u32,
+        end: u32,
+        limit: u32,
+        get_stream: impl Fn(u32) -> Stream,
+        is_matched: impl Fn(&Stream) -> bool,
+    ) -> (Vec<Stream>, Option<u32>) {
+        // Scanning is limited to fit into view gas
+        // when the filter matches rarely.
+        let scan_end = min(end, cursor.saturating_add(MAX_VIEW_STREAMS_SCAN));
+        let mut cursor = cursor;
+        let mut streams = vec![];
+        while cursor < scan_end && (streams.len() as u32) < limit {
+            let stream = get_stream(cursor);
+            cursor += 1;
+            if is_matched(&stream) {
+                streams.push(stream);
+            }
+        }
+        (streams, if cursor < end { Some(cursor) } else { None })
+    }
 }
@@ -1,14 +1,19 @@
-use std::cmp::min;
+use std::cmp::{max, min};
 use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 
 mod account;
 mod dao;
 mod interface;
+mod nft;
+mod operator;
 mod stats;
+mod storage;
 mod stream;
 mod stream_ops;
+mod subscription;
 mod token;
+mod treasury;
 mod unit_tests;
 mod web4;
 
@@ -16,21 +21,28 @@ pub use crate::account::*;
 pub use crate::dao::*;
 pub use crate::interface::token_calls::*;
 pub use crate::interface::views::*;
+pub use crate::nft::*;
+pub use crate::operator::*;
 pub use crate::stats::*;
 pub use crate::stream::*;
+pub use crate::subscription::*;
 pub use crate::token::*;
+pub use crate::treasury::*;
 
 pub use common::*;
 
 use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_contract_standards::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageManagement,
+};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, UnorderedMap, UnorderedSet};
+use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, UnorderedSet};
 use near_sdk::json_types::{Base58CryptoHash, U128};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::serde_json;
 use near_sdk::{
     env, ext_contract, log, near_bindgen, AccountId, Balance, BorshStorageKey, CryptoHash, Gas,
-    PanicOnDefault, Promise, PromiseOrValue, Timestamp, ONE_YOCTO,
+    PanicOnDefault, Promise, PromiseError, PromiseOrValue, StorageUsage, Timestamp, ONE_YOCTO,
 };
 
 #[derive(BorshSerialize, BorshStorageKey)]
@@ -42,6 +54,18 @@ enum StorageKey {
     ActiveOutgoingStreams { account_id: AccountId },
     InactiveIncomingStreams { account_id: AccountId },
     InactiveOutgoingStreams { account_id: AccountId },
+    ArchivedStreams,
+    TokenStreams,
+    ActiveTokenStreams { token_account_id: AccountId },
+    InactiveTokenStreams { token_account_id: AccountId },
+    StatsHistory,
+    TvlReconciliations,
+    Subscriptions,
+    IncomingSubscriptions { account_id: AccountId },
+    OutgoingSubscriptions { account_id: AccountId },
+    StreamNfts,
+    OwnerStreamNfts,
+    OwnerStreamNftIds { account_id: AccountId },
 }
 
 #[near_bindgen]
@@ -52,6 +76,18 @@ pub struct Contract {
     pub accounts: UnorderedMap<AccountId, VAccount>,
     pub streams: UnorderedMap<StreamId, VStream>,
     pub stats: LazyOption<VStats>,
+    pub archived_streams: UnorderedMap<StreamId, ArchivedStream>,
+    pub token_streams: UnorderedMap<AccountId, TokenStreams>,
+    pub stats_history: LookupMap<(AccountId, u32), StatsBucket>,
+    pub tvl_reconciliations: LookupMap<AccountId, TvlReconciliation>,
+    pub subscriptions: UnorderedMap<SubscriptionId, VSubscription>,
+    // Whether commission is refunded to the owner of rejected streams
+    pub refund_commission_on_reject: bool,
+    // NEP-171 tokens minted for receivership of streams
+    pub stream_nfts: UnorderedMap<StreamId, StreamNft>,
+    // Stream NFTs by their owners, the receivers of the streams
+    pub owner_stream_nfts: LookupMap<AccountId, UnorderedSet<StreamId>>,
+    pub stream_status_counts: StreamStatusCounts,
 }
 
 #[near_bindgen]
@@ -69,6 +105,15 @@ impl Contract {
             accounts: UnorderedMap::new(StorageKey::Accounts),
             streams: UnorderedMap::new(StorageKey::Streams),
             stats: LazyOption::new(StorageKey::Stats, Some(&Stats::default().into())),
+            archived_streams: UnorderedMap::new(StorageKey::ArchivedStreams),
+            token_streams: UnorderedMap::new(StorageKey::TokenStreams),
+            stats_history: LookupMap::new(StorageKey::StatsHistory),
+            tvl_reconciliations: LookupMap::new(StorageKey::TvlReconciliations),
+            subscriptions: UnorderedMap::new(StorageKey::Subscriptions),
+            refund_commission_on_reject: false,
+            stream_nfts: UnorderedMap::new(StorageKey::StreamNfts),
+            owner_stream_nfts: LookupMap::new(StorageKey::OwnerStreamNfts),
+            stream_status_counts: StreamStatusCounts::default(),
         }
     }
 
@@ -77,7 +122,7 @@ impl Contract {
     pub fn upgrade() -> Self {
         #[derive(BorshDeserialize)]
         pub struct OldContract {
-            pub dao: Dao,
+            pub dao: DaoV0,
             pub finance_id: AccountId,
             pub accounts: UnorderedMap<AccountId, VAccount>,
             pub streams: UnorderedMap<StreamId, VStream>,
@@ -93,11 +138,23 @@ impl Contract {
         } = env::state_read().unwrap();
 
         Self {
-            dao,
+            dao: dao.into(),
             finance_id,
             accounts,
             streams,
             stats,
+            archived_streams: UnorderedMap::new(StorageKey::ArchivedStreams),
+            token_streams: UnorderedMap::new(StorageKey::TokenStreams),
+            stats_history: LookupMap::new(StorageKey::StatsHistory),
+            tvl_reconciliations: LookupMap::new(StorageKey::TvlReconciliations),
+            subscriptions: UnorderedMap::new(StorageKey::Subscriptions),
+            refund_commission_on_reject: false,
+            stream_nfts: UnorderedMap::new(StorageKey::StreamNfts),
+            owner_stream_nfts: LookupMap::new(StorageKey::OwnerStreamNfts),
+            // Stored streams are not counted here, as reading all of them
+            // may not fit into the gas limit. Each of them is counted
+            // once saved or migrated by `dao_migrate_streams`.
+            stream_status_counts: StreamStatusCounts::default(),
         }
     }
 }
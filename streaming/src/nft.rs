@@ -0,0 +1,500 @@
+use crate::*;
+
+use near_contract_standards::non_fungible_token::events::{NftBurn, NftMint, NftTransfer};
+pub use near_contract_standards::non_fungible_token::metadata::{
+    NFTContractMetadata, TokenMetadata, NFT_METADATA_SPEC,
+};
+pub use near_contract_standards::non_fungible_token::{Token as NftToken, TokenId};
+
+// Stream NFT is a NEP-171 token of the receivership of the stream,
+// minted by the receiver on demand.
+//
+// The token id is the stream id and the token owner is the receiver
+// of the stream, so transferring the token changes the receiver.
+// The token is burnt once the stream is terminated.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct StreamNft {
+    // Storage used by the token is charged to the minter
+    pub minter_id: AccountId,
+    pub storage_usage: StorageUsage,
+
+    // Approvals are given by the owner and charged to the owner,
+    // all of them are revoked when the token is transferred.
+    pub approved_account_ids: HashMap<AccountId, u64>,
+    pub next_approval_id: u64,
+    pub approvals_storage_usage: StorageUsage,
+}
+
+#[ext_contract]
+pub trait ExtNftReceiver {
+    fn nft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_id: AccountId,
+        token_id: TokenId,
+        msg: String,
+    ) -> PromiseOrValue<bool>;
+}
+
+#[ext_contract]
+pub trait ExtNftApprovalReceiver {
+    fn nft_on_approve(
+        &mut self,
+        token_id: TokenId,
+        owner_id: AccountId,
+        approval_id: u64,
+        msg: String,
+    ) -> PromiseOrValue<String>;
+}
+
+#[ext_contract]
+pub trait ExtNftResolver {
+    fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+    ) -> bool;
+}
+
+pub fn stream_nft_token_id(stream_id: &StreamId) -> TokenId {
+    (&Base58CryptoHash::from(*stream_id)).into()
+}
+
+impl Contract {
+    pub(crate) fn mint_stream_nft_op(
+        &mut self,
+        sender_id: &AccountId,
+        stream_id: CryptoHash,
+    ) -> Result<TokenId, ContractError> {
+        let stream = self.view_stream(&stream_id)?;
+
+        if stream.receiver_id != *sender_id {
+            return Err(ContractError::CallerIsNotStreamReceiver {
+                expected: stream.receiver_id,
+                received: sender_id.clone(),
+            });
+        }
+        if stream.status.is_terminated() {
+            return Err(ContractError::StreamTerminated { stream_id });
+        }
+        // Receiver of locked stream can't be changed
+        if stream.is_locked {
+            return Err(ContractError::StreamLocked { stream_id });
+        }
+        if self.stream_nfts.get(&stream_id).is_some() {
+            return Err(ContractError::StreamNftExists { stream_id });
+        }
+
+        // Validations passed
+
+        let storage_usage_initial = env::storage_usage();
+        let mut stream_nft = StreamNft {
+            minter_id: sender_id.clone(),
+            storage_usage: 0,
+            approved_account_ids: HashMap::new(),
+            next_approval_id: 0,
+            approvals_storage_usage: 0,
+        };
+        self.stream_nfts.insert(&stream_id, &stream_nft);
+        self.index_stream_nft(sender_id, &stream_id);
+        // The usage is stored in place of zero, so the size is the same
+        stream_nft.storage_usage = env::storage_usage() - storage_usage_initial;
+        self.stream_nfts.insert(&stream_id, &stream_nft);
+
+        let mut minter = self.extract_account(sender_id)?;
+        minter.charge_storage(stream_nft.storage_usage)?;
+        self.save_account(minter)?;
+
+        let token_id = stream_nft_token_id(&stream_id);
+        NftMint {
+            owner_id: sender_id,
+            token_ids: &[&token_id],
+            memo: None,
+        }
+        .emit();
+
+        Ok(token_id)
+    }
+
+    // Changes the receiver of the stream on behalf of the owner of the token.
+    //
+    // NEAR needed for storage deposit of the new receiver
+    // and for the account creation commission is taken
+    // from the deposit of the previous receiver.
+    pub(crate) fn nft_transfer_op(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: AccountId,
+        token_id: &TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) -> Result<Vec<Promise>, ContractError> {
+        let (stream, stream_nft) = self.view_stream_nft(token_id)?;
+        let owner_id = stream.receiver_id.clone();
+
+        if *sender_id != owner_id {
+            match stream_nft.approved_account_ids.get(sender_id) {
+                Some(id) if approval_id.is_none_or(|approval_id| approval_id == *id) => {}
+                _ => {
+                    return Err(ContractError::StreamNftNotApproved {
+                        account_id: sender_id.clone(),
+                    })
+                }
+            }
+        }
+        if receiver_id == owner_id {
+            return Err(ContractError::InvalidStreamNftReceiver);
+        }
+        // The lender's share is taken from the payments of the stream
+        if stream.pledge.is_some() {
+            return Err(ContractError::StreamPledged {
+                stream_id: stream.id,
+            });
+        }
+        // Tokens accrued are kept to be split by the arbiter
+        if matches!(stream.status, StreamStatus::Disputed { .. }) {
+            return Err(ContractError::CannotTransferStream {
+                stream_status: stream.status,
+            });
+        }
+        stream.check_not_frozen()?;
+
+        let token = self.dao.get_token(&stream.token_account_id);
+        let deposit_needed = if is_aurora_address(&receiver_id) {
+            ONE_YOCTO
+        } else {
+            token.storage_balance_needed
+        };
+        let deposit = if !token.is_payment && self.accounts.get(&receiver_id).is_none() {
+            self.dao.commission_non_payment_ft + deposit_needed
+        } else {
+            deposit_needed
+        };
+        let owner = self.view_account(&owner_id, true)?;
+        if owner.deposit < deposit {
+            return Err(ContractError::InsufficientNearBalance {
+                requested: deposit,
+                left: owner.deposit,
+            });
+        }
+
+        // Validations passed
+
+        let mut owner = self.extract_account(&owner_id)?;
+        owner.deposit -= deposit;
+        self.save_account(owner)?;
+
+        let mut promises = if stream.status == StreamStatus::Active {
+            // Tokens accrued are paid to the previous receiver
+            self.withdraw_op(&owner_id, stream.id)?
+        } else {
+            vec![]
+        };
+        promises.extend(self.process_change_receiver(
+            &owner_id,
+            stream.id,
+            receiver_id.clone(),
+            deposit_needed,
+            deposit,
+        )?);
+        if self.view_account(&receiver_id, true)?.require_accept {
+            promises.extend(self.make_stream_pending(&stream.id)?);
+        }
+
+        NftTransfer {
+            old_owner_id: &owner_id,
+            new_owner_id: &receiver_id,
+            token_ids: &[token_id],
+            authorized_id: (*sender_id != owner_id).then_some(sender_id),
+            memo: memo.as_deref(),
+        }
+        .emit();
+
+        Ok(promises)
+    }
+
+    // Transfers the token back to the previous owner if the receiver
+    // of `nft_transfer_call` asked so and still owns the token.
+    //
+    // Returns whether the token is kept by the receiver.
+    pub(crate) fn nft_resolve_transfer_op(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: &TokenId,
+    ) -> Result<bool, ContractError> {
+        let stream = match self.view_stream_nft(token_id) {
+            Ok((stream, _)) if stream.receiver_id == receiver_id => stream,
+            _ => return Ok(true),
+        };
+
+        // Validations passed
+
+        // The stream made pending by the transfer is returned running
+        if matches!(stream.status, StreamStatus::Pending { .. }) {
+            self.accept_stream_op(&receiver_id, stream.id)?;
+        }
+        // The previous owner has the account and storage deposit already.
+        // Tokens accrued since the transfer are not paid to the receiver
+        // who has refused the token.
+        self.process_change_receiver(&receiver_id, stream.id, previous_owner_id.clone(), 0, 0)?;
+
+        NftTransfer {
+            old_owner_id: &receiver_id,
+            new_owner_id: &previous_owner_id,
+            token_ids: &[token_id],
+            authorized_id: None,
+            memo: None,
+        }
+        .emit();
+
+        Ok(false)
+    }
+
+    // Transferred stream awaits acceptance of the new receiver
+    // as if it was created to them. No commission is taken
+    // on transfer, so nothing but the balance is refunded on reject.
+    fn make_stream_pending(&mut self, stream_id: &StreamId) -> Result<Vec<Promise>, ContractError> {
+        let mut stream = self.extract_stream(stream_id)?;
+        let is_active = stream.status == StreamStatus::Active;
+        let promises = if is_active {
+            self.process_action(&mut stream, ActionType::Pause)?
+        } else {
+            vec![]
+        };
+        // The stream may be finished while transferred,
+        // the stream not active is accepted not started
+        if matches!(
+            stream.status,
+            StreamStatus::Initialized | StreamStatus::Paused
+        ) {
+            stream.status = StreamStatus::Pending {
+                is_auto_start_enabled: is_active,
+                commission: 0,
+            };
+        }
+        self.save_stream(stream)?;
+        Ok(promises)
+    }
+
+    // Returns the approval id.
+    pub(crate) fn nft_approve_op(
+        &mut self,
+        sender_id: &AccountId,
+        token_id: &TokenId,
+        account_id: AccountId,
+    ) -> Result<u64, ContractError> {
+        let (stream, mut stream_nft) = self.view_stream_nft(token_id)?;
+
+        if stream.receiver_id != *sender_id {
+            return Err(ContractError::CallerIsNotStreamReceiver {
+                expected: stream.receiver_id,
+                received: sender_id.clone(),
+            });
+        }
+        if !stream_nft.approved_account_ids.contains_key(&account_id)
+            && stream_nft.approved_account_ids.len() >= MAX_STREAM_NFT_APPROVALS
+        {
+            return Err(ContractError::TooManyStreamNftApprovals {
+                max_approvals: MAX_STREAM_NFT_APPROVALS,
+            });
+        }
+
+        // Validations passed
+
+        let approval_id = stream_nft.next_approval_id;
+        stream_nft.next_approval_id += 1;
+        stream_nft
+            .approved_account_ids
+            .insert(account_id, approval_id);
+
+        let storage_usage_initial = env::storage_usage();
+        self.stream_nfts.insert(&stream.id, &stream_nft);
+        let storage_usage = env::storage_usage().saturating_sub(storage_usage_initial);
+        if storage_usage > 0 {
+            stream_nft.approvals_storage_usage += storage_usage;
+            self.stream_nfts.insert(&stream.id, &stream_nft);
+
+            let mut owner = self.extract_account(sender_id)?;
+            owner.charge_storage(storage_usage)?;
+            self.save_account(owner)?;
+        }
+
+        Ok(approval_id)
+    }
+
+    // Revokes the approval of `account_id` or all of them if None.
+    pub(crate) fn nft_revoke_op(
+        &mut self,
+        sender_id: &AccountId,
+        token_id: &TokenId,
+        account_id: Option<AccountId>,
+    ) -> Result<(), ContractError> {
+        let (stream, stream_nft) = self.view_stream_nft(token_id)?;
+
+        if stream.receiver_id != *sender_id {
+            return Err(ContractError::CallerIsNotStreamReceiver {
+                expected: stream.receiver_id,
+                received: sender_id.clone(),
+            });
+        }
+
+        // Validations passed
+
+        match account_id {
+            Some(account_id) => self.revoke_stream_nft_approval(stream_nft, &stream, &account_id),
+            None => self.revoke_stream_nft_approvals(&stream.id, sender_id),
+        }
+    }
+
+    fn revoke_stream_nft_approval(
+        &mut self,
+        mut stream_nft: StreamNft,
+        stream: &Stream,
+        account_id: &AccountId,
+    ) -> Result<(), ContractError> {
+        if stream_nft.approved_account_ids.remove(account_id).is_none() {
+            return Ok(());
+        }
+        let storage_usage_initial = env::storage_usage();
+        self.stream_nfts.insert(&stream.id, &stream_nft);
+        let storage_usage = storage_usage_initial.saturating_sub(env::storage_usage());
+        if storage_usage > 0 {
+            stream_nft.approvals_storage_usage -=
+                min(stream_nft.approvals_storage_usage, storage_usage);
+            self.stream_nfts.insert(&stream.id, &stream_nft);
+
+            let mut owner = self.extract_account(&stream.receiver_id)?;
+            owner.release_storage(storage_usage);
+            self.save_account(owner)?;
+        }
+        Ok(())
+    }
+
+    // Revokes approvals given by `owner_id`, if the token is minted.
+    pub(crate) fn revoke_stream_nft_approvals(
+        &mut self,
+        stream_id: &StreamId,
+        owner_id: &AccountId,
+    ) -> Result<(), ContractError> {
+        let mut stream_nft = match self.stream_nfts.get(stream_id) {
+            Some(stream_nft) if !stream_nft.approved_account_ids.is_empty() => stream_nft,
+            _ => return Ok(()),
+        };
+        let storage_usage = stream_nft.approvals_storage_usage;
+        stream_nft.approved_account_ids.clear();
+        stream_nft.approvals_storage_usage = 0;
+        self.stream_nfts.insert(stream_id, &stream_nft);
+
+        let mut owner = self.extract_account(owner_id)?;
+        owner.release_storage(storage_usage);
+        self.save_account(owner)
+    }
+
+    pub(crate) fn burn_stream_nft(&mut self, stream: &Stream) -> Result<(), ContractError> {
+        let stream_nft = match self.stream_nfts.remove(&stream.id) {
+            Some(stream_nft) => stream_nft,
+            None => return Ok(()),
+        };
+        self.unindex_stream_nft(&stream.receiver_id, &stream.id);
+
+        let mut minter = self.extract_account(&stream_nft.minter_id)?;
+        minter.release_storage(stream_nft.storage_usage);
+        self.save_account(minter)?;
+        let mut owner = self.extract_account(&stream.receiver_id)?;
+        owner.release_storage(stream_nft.approvals_storage_usage);
+        self.save_account(owner)?;
+
+        NftBurn {
+            owner_id: &stream.receiver_id,
+            token_ids: &[&stream_nft_token_id(&stream.id)],
+            authorized_id: None,
+            memo: None,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    // Moves the token in the owner index, if the token is minted.
+    pub(crate) fn transfer_stream_nft_index(
+        &mut self,
+        stream_id: &StreamId,
+        prev_owner_id: &AccountId,
+        new_owner_id: &AccountId,
+    ) {
+        if self.stream_nfts.get(stream_id).is_some() {
+            self.unindex_stream_nft(prev_owner_id, stream_id);
+            self.index_stream_nft(new_owner_id, stream_id);
+        }
+    }
+
+    fn index_stream_nft(&mut self, owner_id: &AccountId, stream_id: &StreamId) {
+        let mut stream_ids = self.owner_stream_nfts.get(owner_id).unwrap_or_else(|| {
+            UnorderedSet::new(StorageKey::OwnerStreamNftIds {
+                account_id: owner_id.clone(),
+            })
+        });
+        stream_ids.insert(stream_id);
+        self.owner_stream_nfts.insert(owner_id, &stream_ids);
+    }
+
+    fn unindex_stream_nft(&mut self, owner_id: &AccountId, stream_id: &StreamId) {
+        let mut stream_ids = match self.owner_stream_nfts.get(owner_id) {
+            Some(stream_ids) => stream_ids,
+            None => return,
+        };
+        stream_ids.remove(stream_id);
+        if stream_ids.is_empty() {
+            self.owner_stream_nfts.remove(owner_id);
+        } else {
+            self.owner_stream_nfts.insert(owner_id, &stream_ids);
+        }
+    }
+
+    pub(crate) fn view_stream_nft(
+        &self,
+        token_id: &TokenId,
+    ) -> Result<(Stream, StreamNft), ContractError> {
+        let stream_nft_not_exist = || ContractError::StreamNftNotExist {
+            token_id: token_id.clone(),
+        };
+        let stream_id: StreamId = Base58CryptoHash::try_from(token_id.as_str())
+            .map_err(|_| stream_nft_not_exist())?
+            .into();
+        let stream_nft = self
+            .stream_nfts
+            .get(&stream_id)
+            .ok_or_else(stream_nft_not_exist)?;
+        Ok((self.view_stream(&stream_id)?, stream_nft))
+    }
+
+    // Token metadata is generated from the current state of the stream.
+    pub(crate) fn stream_nft_token(&self, stream: &Stream, stream_nft: StreamNft) -> NftToken {
+        let description = format!(
+            "Stream of {} tokens per second of {} from {} to {}",
+            stream.tokens_per_sec, stream.token_account_id, stream.owner_id, stream.receiver_id
+        );
+        let token_id = stream_nft_token_id(&stream.id);
+        NftToken {
+            token_id: token_id.clone(),
+            owner_id: stream.receiver_id.clone(),
+            metadata: Some(TokenMetadata {
+                title: Some(format!("Roketo stream {token_id}")),
+                description: Some(description),
+                media: None,
+                media_hash: None,
+                copies: Some(1),
+                issued_at: None,
+                expires_at: None,
+                starts_at: None,
+                updated_at: None,
+                extra: serde_json::to_string(stream).ok(),
+                reference: None,
+                reference_hash: None,
+            }),
+            approved_account_ids: Some(stream_nft.approved_account_ids),
+        }
+    }
+}
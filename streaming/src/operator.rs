@@ -0,0 +1,101 @@
+use crate::*;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub enum OperatorAction {
+    Withdraw,
+    Pause,
+    Start,
+    Stop,
+    ChangeDescription,
+}
+
+// Approval for the operator to act on behalf of the account
+// with streams the account owns or receives.
+//
+// Deposits are accepted from anyone, so they need no approval.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub struct Operator {
+    pub actions: Vec<OperatorAction>,
+
+    // If set, the approval covers the stream or the streams of the token only
+    pub stream_id: Option<Base58CryptoHash>,
+    pub token_account_id: Option<AccountId>,
+
+    pub expires_at: Option<Timestamp>,
+}
+
+impl Operator {
+    fn allows(&self, action: OperatorAction, stream: &Stream) -> bool {
+        self.actions.contains(&action)
+            && self
+                .stream_id
+                .is_none_or(|stream_id| CryptoHash::from(stream_id) == stream.id)
+            && self
+                .token_account_id
+                .as_ref()
+                .is_none_or(|token_account_id| *token_account_id == stream.token_account_id)
+            && self
+                .expires_at
+                .is_none_or(|expires_at| env::block_timestamp() < expires_at)
+    }
+}
+
+impl Contract {
+    pub(crate) fn add_operator_op(
+        &mut self,
+        account_id: &AccountId,
+        operator_id: AccountId,
+        operator: Operator,
+    ) -> Result<(), ContractError> {
+        if operator_id == *account_id
+            || operator.actions.is_empty()
+            || operator
+                .expires_at
+                .is_some_and(|expires_at| expires_at <= env::block_timestamp())
+        {
+            return Err(ContractError::InvalidOperator);
+        }
+
+        self.create_account_if_not_exist(account_id)?;
+        let mut account = self.extract_account(account_id)?;
+        // Approvals are stored within the account
+        if !account.operators.contains_key(&operator_id) && account.operators.len() >= MAX_OPERATORS
+        {
+            return Err(ContractError::TooManyOperators {
+                max_operators: MAX_OPERATORS,
+            });
+        }
+        account.operators.insert(operator_id, operator);
+        self.save_account(account)
+    }
+
+    pub(crate) fn revoke_operator_op(
+        &mut self,
+        account_id: &AccountId,
+        operator_id: AccountId,
+    ) -> Result<(), ContractError> {
+        let mut account = self.extract_account(account_id)?;
+        if account.operators.remove(&operator_id).is_none() {
+            return Err(ContractError::OperatorNotExist { operator_id });
+        }
+        self.save_account(account)
+    }
+
+    // Whether the sender is approved by the account to make the action with the stream.
+    pub(crate) fn is_operator(
+        &self,
+        account_id: &AccountId,
+        sender_id: &AccountId,
+        action: OperatorAction,
+        stream: &Stream,
+    ) -> bool {
+        self.view_account(account_id, true)
+            .ok()
+            .and_then(|account| account.operators.get(sender_id).cloned())
+            .is_some_and(|operator| operator.allows(action, stream))
+    }
+}
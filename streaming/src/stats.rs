@@ -1,3 +1,5 @@
+use near_contract_standards::fungible_token::core::ext_ft_core;
+
 use crate::*;
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Default, Clone)]
@@ -33,6 +35,8 @@ pub struct Stats {
     #[borsh_skip]
     pub total_streams: u32,
     #[borsh_skip]
+    pub total_archived_streams: u32,
+    #[borsh_skip]
     pub total_dao_tokens: u32,
 
     pub total_active_streams: u32,
@@ -47,6 +51,53 @@ pub struct Stats {
     pub last_update_time: Timestamp,
 }
 
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Default, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct StatsBucket {
+    // Days are counted since unix epoch
+    pub day: u32,
+
+    #[serde(with = "u128_dec_format")]
+    pub deposits: Balance,
+    #[serde(with = "u128_dec_format")]
+    pub transferred: Balance,
+    #[serde(with = "u128_dec_format")]
+    pub refunded: Balance,
+    #[serde(with = "u128_dec_format")]
+    pub commission: Balance,
+
+    pub streams_created: u32,
+    pub peak_active_streams: u32,
+}
+
+// Progress of comparing TVL of the token with actual balances.
+// Streams may change between pages, so the result is exact
+// only if the token streams are not touched meanwhile.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct TvlReconciliation {
+    pub token_account_id: AccountId,
+    // Index of the next stream to scan in the token index,
+    // none if all streams are scanned
+    pub next_index: Option<u32>,
+
+    #[serde(with = "u128_dec_format")]
+    pub streams_balance: Balance,
+    // Held by finance contract as well
+    #[serde(with = "u128_dec_format")]
+    pub treasury_balance: Balance,
+    #[serde(with = "u128_dec_format")]
+    pub tvl: Balance,
+    pub finance_balance: Option<U128>,
+}
+
+#[ext_contract(ext_self)]
+pub trait ExtReconcileTvl {
+    fn on_reconcile_tvl(&mut self, reconciliation: TvlReconciliation) -> TvlReconciliation;
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Clone)]
 pub enum VStats {
     Current(Stats),
@@ -67,6 +118,33 @@ impl From<Stats> for VStats {
 }
 
 impl Contract {
+    // Updates today's bucket of the token,
+    // the bucket of the same day a year ago is reset.
+    fn stats_history_update(
+        &mut self,
+        token_account_id: &AccountId,
+        f: impl FnOnce(&mut StatsBucket),
+    ) {
+        let stats: Stats = self.stats.get().unwrap().into();
+        let active_streams = match stats.dao_tokens.get(token_account_id) {
+            Some(token_stats) => token_stats.active_streams,
+            None => return,
+        };
+        let day = (env::block_timestamp() / TICKS_PER_SECOND / SECONDS_PER_DAY) as u32;
+        let key = (token_account_id.clone(), day % STATS_HISTORY_DAYS);
+        let mut bucket = match self.stats_history.get(&key) {
+            Some(bucket) if bucket.day == day => bucket,
+            _ => StatsBucket {
+                day,
+                peak_active_streams: active_streams,
+                ..Default::default()
+            },
+        };
+        f(&mut bucket);
+        bucket.peak_active_streams = max(bucket.peak_active_streams, active_streams);
+        self.stats_history.insert(&key, &bucket);
+    }
+
     pub(crate) fn stats_add_token(&mut self, token_account_id: &AccountId) {
         let mut stats: Stats = self.stats.take().unwrap().into();
         assert!(stats
@@ -99,6 +177,7 @@ impl Contract {
             });
         stats.last_update_time = env::block_timestamp();
         self.stats.set(&stats.into());
+        self.stats_history_update(token_account_id, |e| e.streams_created += 1);
     }
 
     pub(crate) fn stats_inc_active_streams(&mut self, token_account_id: &AccountId) {
@@ -113,6 +192,7 @@ impl Contract {
             });
         stats.last_update_time = env::block_timestamp();
         self.stats.set(&stats.into());
+        self.stats_history_update(token_account_id, |_| {});
     }
 
     pub(crate) fn stats_dec_active_streams(&mut self, token_account_id: &AccountId) {
@@ -147,6 +227,10 @@ impl Contract {
             });
         stats.last_update_time = env::block_timestamp();
         self.stats.set(&stats.into());
+        self.stats_history_update(token_account_id, |e| {
+            e.deposits += deposit;
+            e.commission += commission;
+        });
     }
 
     pub(crate) fn stats_withdraw(&mut self, token: &Token, payment: Balance, commission: Balance) {
@@ -164,6 +248,10 @@ impl Contract {
             stats.last_update_time = env::block_timestamp();
         }
         self.stats.set(&stats.into());
+        self.stats_history_update(&token.account_id, |e| {
+            e.transferred += payment;
+            e.commission += commission;
+        });
     }
 
     pub(crate) fn stats_refund(&mut self, token: &Token, refund: Balance) {
@@ -180,6 +268,27 @@ impl Contract {
             stats.last_update_time = env::block_timestamp();
         }
         self.stats.set(&stats.into());
+        self.stats_history_update(&token.account_id, |e| e.refunded += refund);
+    }
+
+    // Commission is refunded from the tokens collected by the contract.
+    pub(crate) fn stats_refund_commission(
+        &mut self,
+        token_account_id: &AccountId,
+        commission: Balance,
+    ) {
+        let mut stats: Stats = self.stats.take().unwrap().into();
+        stats
+            .dao_tokens
+            .entry(token_account_id.clone())
+            .and_modify(|e| {
+                e.total_commission_collected -= commission;
+                e.refunded += commission;
+                e.last_update_time = env::block_timestamp();
+            });
+        stats.last_update_time = env::block_timestamp();
+        self.stats.set(&stats.into());
+        self.stats_history_update(token_account_id, |e| e.refunded += commission);
     }
 
     pub(crate) fn stats_inc_account_deposit(&mut self, deposit: Balance, is_aurora: bool) {
@@ -192,4 +301,68 @@ impl Contract {
         stats.last_update_time = env::block_timestamp();
         self.stats.set(&stats.into());
     }
+
+    pub(crate) fn reconcile_tvl_op(
+        &mut self,
+        token_account_id: AccountId,
+        limit: u32,
+    ) -> Result<PromiseOrValue<TvlReconciliation>, ContractError> {
+        let mut reconciliation = self
+            .tvl_reconciliations
+            .remove(&token_account_id)
+            .unwrap_or(TvlReconciliation {
+                token_account_id: token_account_id.clone(),
+                next_index: Some(0),
+                streams_balance: 0,
+                treasury_balance: 0,
+                tvl: 0,
+                finance_balance: None,
+            });
+
+        // Only non-terminated streams have balance, they're indexed by token.
+        // The index is not reordered by actions keeping the status of streams.
+        let token_streams = self
+            .token_streams
+            .get(&token_account_id)
+            .unwrap_or_else(|| TokenStreams::new(&token_account_id));
+        let total_len =
+            (token_streams.active_streams.len() + token_streams.inactive_streams.len()) as u32;
+        let from = reconciliation.next_index.unwrap_or(0);
+        let to = min(total_len, from.saturating_add(limit));
+        for i in from..to {
+            let stream = self.get_indexed_stream(
+                &token_streams.active_streams,
+                &token_streams.inactive_streams,
+                i,
+            );
+            reconciliation.streams_balance += stream.balance;
+        }
+
+        if to < total_len {
+            reconciliation.next_index = Some(to);
+            self.tvl_reconciliations
+                .insert(&token_account_id, &reconciliation);
+            return Ok(PromiseOrValue::Value(reconciliation));
+        }
+
+        // TVL is tracked for listed tokens only
+        let stats: Stats = self.stats.get().unwrap().into();
+        reconciliation.next_index = None;
+        reconciliation.treasury_balance = token_streams.treasury_balance;
+        reconciliation.tvl = stats
+            .dao_tokens
+            .get(&token_account_id)
+            .map_or(0, |token_stats| token_stats.tvl);
+
+        check_gas(GAS_FOR_FT_BALANCE_OF + GAS_FOR_ON_RECONCILE_TVL)?;
+        let promise = ext_ft_core::ext(token_account_id)
+            .with_static_gas(GAS_FOR_FT_BALANCE_OF)
+            .ft_balance_of(self.finance_id.clone())
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_RECONCILE_TVL)
+                    .on_reconcile_tvl(reconciliation),
+            );
+        Ok(PromiseOrValue::Promise(promise))
+    }
 }
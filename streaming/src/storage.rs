@@ -0,0 +1,238 @@
+use crate::*;
+
+#[near_bindgen]
+impl StorageManagement for Contract {
+    // NEP-145 interface
+    #[payable]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        self.storage_deposit_op(
+            &account_id.unwrap_or_else(env::predecessor_account_id),
+            env::attached_deposit(),
+            registration_only.unwrap_or(false),
+        )
+        .unwrap()
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        check_deposit(ONE_YOCTO).unwrap();
+        self.storage_withdraw_op(&env::predecessor_account_id(), amount.map(|a| a.into()))
+            .unwrap()
+    }
+
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        check_deposit(ONE_YOCTO).unwrap();
+        self.storage_unregister_op(&env::predecessor_account_id(), force.unwrap_or(false))
+            .unwrap()
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: U128(MIN_STORAGE_BALANCE),
+            max: None,
+        }
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.view_account(&account_id, true)
+            .ok()
+            .filter(|account| account.is_storage_registered())
+            .map(|account| account.storage_balance_view())
+    }
+}
+
+impl Account {
+    pub(crate) fn is_storage_registered(&self) -> bool {
+        self.storage_balance > 0
+    }
+
+    pub(crate) fn storage_available(&self) -> Balance {
+        self.storage_balance.saturating_sub(
+            MIN_STORAGE_BALANCE + self.storage_usage as Balance * env::storage_byte_cost(),
+        )
+    }
+
+    pub(crate) fn storage_balance_view(&self) -> StorageBalance {
+        StorageBalance {
+            total: U128(self.storage_balance),
+            available: U128(self.storage_available()),
+        }
+    }
+
+    pub(crate) fn charge_storage(
+        &mut self,
+        storage_usage: StorageUsage,
+    ) -> Result<(), ContractError> {
+        let storage_needed = MIN_STORAGE_BALANCE
+            + (self.storage_usage + storage_usage) as Balance * env::storage_byte_cost();
+        if storage_needed > self.storage_balance {
+            return Err(ContractError::InsufficientStorageBalance {
+                requested: storage_needed,
+                left: self.storage_balance,
+            });
+        }
+        self.storage_usage += storage_usage;
+        Ok(())
+    }
+
+    pub(crate) fn release_storage(&mut self, storage_usage: StorageUsage) {
+        self.storage_usage -= min(self.storage_usage, storage_usage);
+    }
+}
+
+impl Contract {
+    pub(crate) fn storage_deposit_op(
+        &mut self,
+        account_id: &AccountId,
+        deposit: Balance,
+        registration_only: bool,
+    ) -> Result<StorageBalance, ContractError> {
+        self.create_account_if_not_exist(account_id)?;
+        let mut account = self.extract_account(account_id)?;
+
+        let refund = if account.is_storage_registered() {
+            if registration_only {
+                deposit
+            } else {
+                account.storage_balance += deposit;
+                0
+            }
+        } else {
+            if deposit < MIN_STORAGE_BALANCE {
+                return Err(ContractError::InsufficientDeposit {
+                    expected: MIN_STORAGE_BALANCE,
+                    received: deposit,
+                });
+            }
+            if registration_only {
+                account.storage_balance = MIN_STORAGE_BALANCE;
+                deposit - MIN_STORAGE_BALANCE
+            } else {
+                account.storage_balance = deposit;
+                0
+            }
+        };
+
+        let storage_balance = account.storage_balance_view();
+        self.save_account(account)?;
+
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+
+        Ok(storage_balance)
+    }
+
+    pub(crate) fn storage_withdraw_op(
+        &mut self,
+        account_id: &AccountId,
+        amount: Option<Balance>,
+    ) -> Result<StorageBalance, ContractError> {
+        let mut account = self.extract_account(account_id)?;
+        if !account.is_storage_registered() {
+            return Err(ContractError::AccountNotRegistered {
+                account_id: account_id.clone(),
+            });
+        }
+
+        let available = account.storage_available();
+        let amount = amount.unwrap_or(available);
+        if amount > available {
+            return Err(ContractError::InsufficientStorageBalance {
+                requested: amount,
+                left: available,
+            });
+        }
+        account.storage_balance -= amount;
+
+        let storage_balance = account.storage_balance_view();
+        self.save_account(account)?;
+
+        if amount > 0 {
+            Promise::new(account_id.clone()).transfer(amount);
+        }
+
+        Ok(storage_balance)
+    }
+
+    pub(crate) fn storage_unregister_op(
+        &mut self,
+        account_id: &AccountId,
+        force: bool,
+    ) -> Result<bool, ContractError> {
+        let account = match self.view_account(account_id, true) {
+            Ok(account) if account.is_storage_registered() => account,
+            _ => return Ok(false),
+        };
+
+        // Streams and subscriptions are referring the account in any case,
+        // so the account cannot be removed even with force.
+        if account.storage_usage > 0
+            || !account.active_incoming_streams.is_empty()
+            || !account.active_outgoing_streams.is_empty()
+            || !account.inactive_incoming_streams.is_empty()
+            || !account.inactive_outgoing_streams.is_empty()
+            || account
+                .treasury
+                .values()
+                .any(|treasury| treasury.balance > 0 || treasury.outflow > 0)
+            || !account.incoming_subscriptions.is_empty()
+            || !account.outgoing_subscriptions.is_empty()
+        {
+            return Err(ContractError::CannotUnregisterAccount {
+                account_id: account_id.clone(),
+            });
+        }
+        // With force, NEAR deposit and stake are burnt.
+        if !force && (account.deposit > 0 || account.stake > 0) {
+            return Err(ContractError::CannotUnregisterAccount {
+                account_id: account_id.clone(),
+            });
+        }
+
+        let account = self.extract_account(account_id)?;
+        Promise::new(account_id.clone()).transfer(account.storage_balance);
+
+        Ok(true)
+    }
+
+    // Charges the creator of the stream for the storage used
+    // since `storage_usage_initial` was measured and for the accounts
+    // created along with the stream.
+    //
+    // The accounts are recorded as the storage of the stream,
+    // so all of it is released once the stream is pruned.
+    //
+    // Returns false if the creator is not registered,
+    // so the storage must be covered by finance contract.
+    pub(crate) fn charge_stream_storage(
+        &mut self,
+        stream_id: &StreamId,
+        storage_usage_initial: StorageUsage,
+        storage_usage_accounts: StorageUsage,
+    ) -> Result<bool, ContractError> {
+        let storage_usage = env::storage_usage().saturating_sub(storage_usage_initial);
+
+        let mut stream = self.extract_stream(stream_id)?;
+        let mut creator = self.extract_account(&stream.creator_id)?;
+        if !creator.is_storage_registered() {
+            self.save_account(creator)?;
+            self.save_stream(stream)?;
+            return Ok(false);
+        }
+
+        let storage_usage = storage_usage + storage_usage_accounts;
+        creator.charge_storage(storage_usage)?;
+        stream.storage_usage = storage_usage;
+
+        self.save_account(creator)?;
+        self.save_stream(stream)?;
+
+        Ok(true)
+    }
+}
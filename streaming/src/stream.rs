@@ -53,6 +53,11 @@ pub struct Stream {
     //
     // Such behavior called non-expirable streams and disabled by default.
     // Expirable streams will be terminated even on stream depositing.
+    //
+    // Non-expirable streams are not finished when the balance runs out.
+    // Tokens accrued beyond the balance are tracked as `debt`
+    // since `insolvent_since`, and the next deposit pays the debt
+    // to the receiver first. Stopping the stream forgives the debt.
     pub is_expirable: bool,
 
     // Locked streams are ones that are unable to pause, stop and change receiver.
@@ -63,23 +68,204 @@ pub struct Stream {
     // as the final result of locked stream cannot be changed.
     pub is_locked: bool,
 
+    // Streams funded from treasury have no escrowed balance.
+    // Tokens are drawn from the owner's treasury of the stream token
+    // on every payment, so the balance is zero between actions.
+    //
+    // Once the treasury is exhausted, the stream accrues tokens
+    // until the moment of exhaustion only and is finished
+    // on its next action. See `Treasury` for details.
+    pub funded_from_treasury: bool,
+
+    // Streams with approver accrue tokens as usual, but the receiver
+    // may withdraw them up to the last approved milestone only.
+    //
+    // Milestones are cumulative amounts of tokens withdrawn in total,
+    // the last implicit milestone releases the rest of the stream.
+    // Tokens accrued beyond the approved milestone are kept
+    // in `tokens_awaiting_approval` until the next approval.
+    //
+    // Rejection by approver stops the stream, tokens approved are paid
+    // and the rest is refunded to the owner.
+    pub approver_id: Option<AccountId>,
+    #[serde(with = "u128_vec_format")]
+    pub milestones: Vec<Balance>,
+    pub milestones_approved: u32,
+    #[serde(with = "u128_dec_format")]
+    pub tokens_awaiting_approval: Balance,
+
+    // Payments of the stream are deposited to the child stream
+    // owned by the receiver instead of being transferred out,
+    // so the tokens stay in finance contract.
+    //
+    // If the child cannot be deposited, e.g. it's expired or finished,
+    // the payment is transferred to the receiver as usual.
+    pub child_stream_id: Option<Base58CryptoHash>,
+    pub parent_stream_id: Option<Base58CryptoHash>,
+
+    // Debt settled at the last action, see `debt` for the actual one.
+    #[serde(skip)]
+    pub settled_debt: Balance,
+    pub insolvent_since: Option<Timestamp>,
+
+    // Stopping by the owner is restricted by the policy, if any.
+    // The stop requested within the notice period is scheduled
+    // to `stop_at`, nothing is accrued after that moment.
+    pub cancel_policy: Option<CancelPolicy>,
+    pub stop_at: Option<Timestamp>,
+
+    // Streams opted in may be frozen and clawed back by the DAO,
+    // e.g. when the receiver is compromised. Locked streams are exempt.
+    //
+    // Frozen streams accrue nothing after `frozen_at`
+    // and no actions are allowed until unfrozen.
+    pub is_clawback_allowed: bool,
+    pub frozen_at: Option<Timestamp>,
+
+    // Either the owner or the receiver may open a dispute of the stream
+    // with arbiter. The arbiter resolves it by splitting the balance,
+    // including tokens accrued and not withdrawn yet.
+    pub arbiter_id: Option<AccountId>,
+
+    // Receivables of the stream pledged by the receiver to the lender.
+    // The lender takes the share of each payment until repaid,
+    // the receiver can't stop the stream or change the receiver meanwhile.
+    pub pledge: Option<Pledge>,
+
+    // Tokens are accrued with nanosecond precision.
+    // The fraction of a token accrued but not withdrawn yet
+    // is carried over in 1/TICKS_PER_SECOND units,
+    // so the total accrued amount depends only on the time passed.
+    pub accrual_remainder: u64,
+
+    // Storage used by the stream and charged to the creator,
+    // including the accounts created along with the stream.
+    // Zero means the storage is covered by finance contract.
+    pub storage_usage: StorageUsage,
+
     #[borsh_skip]
     #[serde(with = "u128_dec_format")]
     pub available_to_withdraw_by_formula: Balance,
+    #[borsh_skip]
+    #[serde(with = "u128_dec_format")]
+    pub debt: Balance,
+}
+
+// Restrictions on stopping the stream by the owner,
+// the receiver may stop the stream at any time.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct CancelPolicy {
+    // The owner cannot stop the stream before the moment
+    pub no_stop_before: Option<Timestamp>,
+    // Stopping by the owner takes effect once the notice period passes,
+    // the stream keeps streaming meanwhile
+    pub notice_period_sec: Option<u32>,
+    // Paid to the receiver out of the balance when the owner stops the stream
+    #[serde(default, with = "u128_dec_format")]
+    pub severance: Balance,
 }
 
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub struct Pledge {
+    pub lender_id: AccountId,
+    #[serde(with = "u128_dec_format")]
+    pub amount: Balance,
+    #[serde(with = "u128_dec_format")]
+    pub repaid: Balance,
+    // Share of each payment to the receiver in basis points
+    pub share_bps: u16,
+}
+
+// Stream as stored by v2.2.0, see `Stream` for the fields.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct StreamV0 {
+    pub id: CryptoHash,
+    pub description: Option<String>,
+    pub creator_id: AccountId,
+    pub owner_id: AccountId,
+    pub receiver_id: AccountId,
+    pub token_account_id: AccountId,
+
+    pub timestamp_created: Timestamp,
+    pub last_action: Timestamp,
+
+    pub balance: Balance,
+    pub tokens_per_sec: Balance,
+
+    pub status: StreamStatus,
+    pub tokens_total_withdrawn: Balance,
+
+    pub cliff: Option<Timestamp>,
+    pub is_expirable: bool,
+    pub is_locked: bool,
+}
+
+// Variants are never reordered, stored streams are migrated
+// to the current layout on read and saved as current.
+// Legacy variants only exist while being read.
+#[allow(clippy::large_enum_variant)]
 #[derive(BorshSerialize, BorshDeserialize)]
 pub enum VStream {
+    V0(StreamV0),
     Current(Stream),
 }
 
 impl From<VStream> for Stream {
     fn from(v: VStream) -> Self {
-        match v {
-            VStream::Current(mut c) => {
-                c.available_to_withdraw_by_formula = c.available_to_withdraw();
-                c
-            }
+        let mut c = match v {
+            VStream::V0(s) => s.into(),
+            VStream::Current(c) => c,
+        };
+        c.available_to_withdraw_by_formula = c.available_to_withdraw();
+        c.debt = c.debt_at(env::block_timestamp());
+        c.insolvent_since = c.insolvent_since_at(env::block_timestamp());
+        c
+    }
+}
+
+// Streams created before have none of the extra terms,
+// their storage is covered by finance contract.
+impl From<StreamV0> for Stream {
+    fn from(s: StreamV0) -> Self {
+        Self {
+            id: s.id,
+            description: s.description,
+            creator_id: s.creator_id,
+            owner_id: s.owner_id,
+            receiver_id: s.receiver_id,
+            token_account_id: s.token_account_id,
+            timestamp_created: s.timestamp_created,
+            last_action: s.last_action,
+            balance: s.balance,
+            tokens_per_sec: s.tokens_per_sec,
+            status: s.status,
+            tokens_total_withdrawn: s.tokens_total_withdrawn,
+            cliff: s.cliff,
+            is_expirable: s.is_expirable,
+            is_locked: s.is_locked,
+            funded_from_treasury: false,
+            approver_id: None,
+            milestones: vec![],
+            milestones_approved: 0,
+            tokens_awaiting_approval: 0,
+            child_stream_id: None,
+            parent_stream_id: None,
+            settled_debt: 0,
+            insolvent_since: None,
+            cancel_policy: None,
+            stop_at: None,
+            is_clawback_allowed: false,
+            frozen_at: None,
+            arbiter_id: None,
+            pledge: None,
+            accrual_remainder: 0,
+            storage_usage: 0,
+            available_to_withdraw_by_formula: 0,
+            debt: 0,
         }
     }
 }
@@ -90,6 +276,112 @@ impl From<Stream> for VStream {
     }
 }
 
+// Compact record of the stream that is left after pruning.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct ArchivedStream {
+    #[serde(with = "b58_dec_format")]
+    pub id: CryptoHash,
+    pub creator_id: AccountId,
+    pub owner_id: AccountId,
+    pub receiver_id: AccountId,
+    pub token_account_id: AccountId,
+
+    pub timestamp_created: Timestamp,
+    pub timestamp_finished: Timestamp,
+
+    #[serde(with = "u128_dec_format")]
+    pub tokens_total_withdrawn: Balance,
+
+    pub reason: StreamFinishReason,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct StreamResult {
+    #[serde(with = "b58_dec_format")]
+    pub stream_id: CryptoHash,
+    pub result: Result<(), ContractError>,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct BatchResult {
+    pub results: Vec<StreamResult>,
+    // Streams left because of insufficient gas
+    pub unprocessed: Vec<Base58CryptoHash>,
+}
+
+// Secondary index of non-terminated streams by token.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TokenStreams {
+    pub active_streams: UnorderedSet<StreamId>,
+    pub inactive_streams: UnorderedSet<StreamId>,
+
+    // Sum of tokens_per_sec of all active streams
+    pub outflow: Balance,
+    // Tokens in treasuries of owners, not drawn by streams yet
+    pub treasury_balance: Balance,
+}
+
+impl TokenStreams {
+    pub(crate) fn new(token_account_id: &AccountId) -> Self {
+        Self {
+            active_streams: UnorderedSet::new(StorageKey::ActiveTokenStreams {
+                token_account_id: token_account_id.clone(),
+            }),
+            inactive_streams: UnorderedSet::new(StorageKey::InactiveTokenStreams {
+                token_account_id: token_account_id.clone(),
+            }),
+            outflow: 0,
+            treasury_balance: 0,
+        }
+    }
+}
+
+// Number of stored streams by status, archived ones are not counted.
+// Streams stored before the upgrade are counted once migrated.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Default, Clone, PartialEq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct StreamStatusCounts {
+    pub pending: u32,
+    pub initialized: u32,
+    pub active: u32,
+    pub paused: u32,
+    pub disputed: u32,
+    pub finished: u32,
+}
+
+impl StreamStatusCounts {
+    fn count_mut(&mut self, status: &StreamStatus) -> &mut u32 {
+        match status {
+            StreamStatus::Pending { .. } => &mut self.pending,
+            StreamStatus::Initialized => &mut self.initialized,
+            StreamStatus::Active => &mut self.active,
+            StreamStatus::Paused => &mut self.paused,
+            StreamStatus::Disputed { .. } => &mut self.disputed,
+            StreamStatus::Finished { .. } => &mut self.finished,
+        }
+    }
+
+    pub(crate) fn inc(&mut self, status: &StreamStatus) {
+        *self.count_mut(status) += 1;
+    }
+
+    pub(crate) fn dec(&mut self, status: &StreamStatus) {
+        *self.count_mut(status) -= 1;
+    }
+
+    // Non-terminated streams that are not active
+    pub fn inactive(&self) -> u32 {
+        self.pending + self.initialized + self.paused + self.disputed
+    }
+}
+
 impl Stream {
     pub(crate) fn new(
         salt: u64,
@@ -123,12 +415,40 @@ impl Stream {
             cliff,
             is_expirable,
             is_locked,
+            funded_from_treasury: false,
+            approver_id: None,
+            milestones: vec![],
+            milestones_approved: 0,
+            tokens_awaiting_approval: 0,
+            child_stream_id: None,
+            parent_stream_id: None,
+            settled_debt: 0,
+            insolvent_since: None,
+            cancel_policy: None,
+            stop_at: None,
+            is_clawback_allowed: false,
+            frozen_at: None,
+            arbiter_id: None,
+            pledge: None,
+            accrual_remainder: 0,
+            storage_usage: 0,
             available_to_withdraw_by_formula: 0,
+            debt: 0,
         }
     }
 
     pub(crate) fn process_withdraw(&mut self, token: &Token) -> (Balance, Balance) {
-        let mut gross_payment = self.available_to_withdraw();
+        let excess = self.excess_at(env::block_timestamp());
+        if excess > 0 {
+            self.insolvent_since = self.insolvent_since_at(env::block_timestamp());
+            self.settled_debt += excess;
+        }
+        let accrued = self.accrued_total_at(env::block_timestamp());
+        let mut gross_payment = self.released(accrued);
+        self.tokens_awaiting_approval = accrued - gross_payment;
+        if self.status == StreamStatus::Active {
+            self.accrual_remainder = self.accrued_at(env::block_timestamp()).1;
+        }
         assert!(
             gross_payment <= self.balance,
             "available_to_withdraw() must guarantee that gross_payment({}) <= self.balance({})",
@@ -152,7 +472,7 @@ impl Stream {
 
         self.balance -= gross_payment;
 
-        if self.balance == 0 {
+        if self.balance == 0 && !self.funded_from_treasury && self.is_expirable {
             self.status = StreamStatus::Finished {
                 reason: StreamFinishReason::FinishedNaturally,
             };
@@ -165,14 +485,138 @@ impl Stream {
     }
 
     pub(crate) fn available_to_withdraw(&self) -> Balance {
+        self.available_to_withdraw_at(env::block_timestamp())
+    }
+
+    pub(crate) fn available_to_withdraw_at(&self, timestamp: Timestamp) -> Balance {
+        self.released(self.accrued_total_at(timestamp))
+    }
+
+    // Tokens accrued and not withdrawn yet, including ones awaiting approval.
+    fn accrued_total_at(&self, timestamp: Timestamp) -> Balance {
         if self.status == StreamStatus::Active {
-            let period = env::block_timestamp() - self.last_action;
             min(
                 self.balance,
-                (period / TICKS_PER_SECOND) as u128 * self.tokens_per_sec,
+                self.tokens_awaiting_approval + self.accrued_at(timestamp).0,
             )
         } else {
-            0
+            self.tokens_awaiting_approval
+        }
+    }
+
+    // Tokens accrued beyond the balance since the last action.
+    // Only non-expirable streams run on credit.
+    fn excess_at(&self, timestamp: Timestamp) -> Balance {
+        if self.status != StreamStatus::Active || self.is_expirable || self.funded_from_treasury {
+            return 0;
+        }
+        (self.tokens_awaiting_approval + self.accrued_at(timestamp).0).saturating_sub(self.balance)
+    }
+
+    pub(crate) fn debt_at(&self, timestamp: Timestamp) -> Balance {
+        self.settled_debt + self.excess_at(timestamp)
+    }
+
+    // The moment the balance ran out, if the stream is in debt.
+    pub(crate) fn insolvent_since_at(&self, timestamp: Timestamp) -> Option<Timestamp> {
+        match self.insolvent_since {
+            Some(insolvent_since) => Some(insolvent_since),
+            None if self.excess_at(timestamp) > 0 => {
+                Some(self.last_action.saturating_add(accrual_period(
+                    self.balance - self.tokens_awaiting_approval,
+                    self.tokens_per_sec,
+                    self.accrual_remainder,
+                )))
+            }
+            None => None,
+        }
+    }
+
+    // Part of accrued tokens released by approved milestones.
+    pub(crate) fn released(&self, accrued: Balance) -> Balance {
+        let limit = match self.approver_id {
+            None => return accrued,
+            Some(_) => match self.milestones_approved as usize {
+                0 => 0,
+                approved if approved <= self.milestones.len() => self.milestones[approved - 1],
+                _ => return accrued,
+            },
+        };
+        min(accrued, limit.saturating_sub(self.tokens_total_withdrawn))
+    }
+
+    // Tokens accrued since the last action and the new remainder.
+    pub(crate) fn accrued_at(&self, timestamp: Timestamp) -> (Balance, u64) {
+        // Nothing is accrued after the scheduled stop or freezing
+        let timestamp = [self.stop_at, self.frozen_at]
+            .into_iter()
+            .flatten()
+            .fold(timestamp, min);
+        accrue(
+            timestamp.saturating_sub(self.last_action),
+            self.tokens_per_sec,
+            self.accrual_remainder,
+        )
+    }
+
+    // The moment the whole balance becomes available to withdraw.
+    pub(crate) fn run_out_time(&self) -> Timestamp {
+        self.last_action.saturating_add(accrual_period(
+            self.balance,
+            self.tokens_per_sec,
+            self.accrual_remainder,
+        ))
+    }
+
+    // Tokens to be paid by the owner after the moment, the stop
+    // scheduled by the owner pays the severance and refunds the rest.
+    pub(crate) fn outgoing_remaining_at(&self, timestamp: Timestamp) -> Balance {
+        match self.stop_at {
+            None => self.balance - self.available_to_withdraw_at(timestamp),
+            Some(stop_at) if timestamp >= stop_at => 0,
+            Some(stop_at) => {
+                let accrued = self.available_to_withdraw_at(stop_at);
+                let severance = self
+                    .cancel_policy
+                    .as_ref()
+                    .map_or(0, |policy| min(policy.severance, self.balance - accrued));
+                accrued - self.available_to_withdraw_at(timestamp) + severance
+            }
+        }
+    }
+
+    // Whether the stop scheduled by the owner is due.
+    pub(crate) fn is_stop_due(&self) -> bool {
+        self.stop_at
+            .is_some_and(|stop_at| env::block_timestamp() >= stop_at)
+    }
+
+    // Streams with no extra terms may be split and merged,
+    // the cliff should be updated before.
+    pub(crate) fn is_divisible(&self) -> bool {
+        matches!(
+            self.status,
+            StreamStatus::Initialized | StreamStatus::Active | StreamStatus::Paused
+        ) && !self.is_locked
+            && !self.funded_from_treasury
+            && self.approver_id.is_none()
+            && self.cancel_policy.is_none()
+            && self.frozen_at.is_none()
+            && self.pledge.is_none()
+            && self.cliff.is_none()
+            && self.debt_at(env::block_timestamp()) == 0
+            && self.remaining_balance() > 0
+    }
+
+    // Balance left after the accrued tokens are withdrawn.
+    pub(crate) fn remaining_balance(&self) -> Balance {
+        self.balance - self.available_to_withdraw()
+    }
+
+    pub(crate) fn check_not_frozen(&self) -> Result<(), ContractError> {
+        match self.frozen_at {
+            Some(_) => Err(ContractError::StreamFrozen { stream_id: self.id }),
+            None => Ok(()),
         }
     }
 
@@ -185,6 +629,35 @@ impl Stream {
     }
 }
 
+// Tokens accrued over the period and the new remainder.
+// Whole seconds are counted separately to avoid overflow.
+pub(crate) fn accrue(period: u64, tokens_per_sec: Balance, remainder: u64) -> (Balance, u64) {
+    let ticks = TICKS_PER_SECOND as u128;
+    let partial = (period % TICKS_PER_SECOND) as u128 * tokens_per_sec + remainder as u128;
+    (
+        (period / TICKS_PER_SECOND) as u128 * tokens_per_sec + partial / ticks,
+        (partial % ticks) as u64,
+    )
+}
+
+// The shortest period to accrue the amount, saturating.
+pub(crate) fn accrual_period(amount: Balance, tokens_per_sec: Balance, remainder: u64) -> u64 {
+    // Solving amount * TICKS_PER_SECOND <= period * tokens_per_sec + remainder
+    // with amount = seconds * tokens_per_sec + rest.
+    let seconds = amount / tokens_per_sec;
+    if seconds >= (u64::MAX / TICKS_PER_SECOND) as u128 {
+        return u64::MAX;
+    }
+    let rest = (amount % tokens_per_sec) * TICKS_PER_SECOND as u128;
+    let remainder = remainder as u128;
+    if rest >= remainder {
+        seconds as u64 * TICKS_PER_SECOND + (rest - remainder).div_ceil(tokens_per_sec) as u64
+    } else {
+        (seconds as u64 * TICKS_PER_SECOND)
+            .saturating_sub(((remainder - rest) / tokens_per_sec) as u64)
+    }
+}
+
 impl Contract {
     pub(crate) fn process_action(
         &mut self,
@@ -196,6 +669,15 @@ impl Contract {
         let mut owner = self.extract_account(&stream.owner_id)?;
         let mut receiver = self.extract_account(&stream.receiver_id)?;
         let mut promises = vec![];
+        let was_active = match action_type {
+            ActionType::Init => None,
+            _ => Some(stream.status == StreamStatus::Active),
+        };
+        if stream.funded_from_treasury && action_type != ActionType::Init {
+            owner
+                .treasury_mut(&stream.token_account_id)
+                .settle(env::block_timestamp());
+        }
 
         if action_type == ActionType::Init {
             check_integrity(owner.inactive_outgoing_streams.insert(&stream.id))?;
@@ -223,9 +705,12 @@ impl Contract {
                 }
                 ActionType::Pause => {
                     check_integrity(stream.status == StreamStatus::Active)?;
-                    if let Some(promise) = self.process_payment(stream, &mut receiver)? {
-                        promises.push(promise);
-                    }
+                    promises.extend(self.process_payment(
+                        stream,
+                        &mut owner,
+                        &mut receiver,
+                        None,
+                    )?);
                     owner
                         .total_outgoing
                         .entry(stream.token_account_id.clone())
@@ -248,9 +733,12 @@ impl Contract {
                 }
                 ActionType::Stop { reason } => {
                     if stream.status == StreamStatus::Active {
-                        if let Some(promise) = self.process_payment(stream, &mut receiver)? {
-                            promises.push(promise);
-                        }
+                        promises.extend(self.process_payment(
+                            stream,
+                            &mut owner,
+                            &mut receiver,
+                            None,
+                        )?);
                         check_integrity(owner.active_outgoing_streams.remove(&stream.id))?;
                         check_integrity(receiver.active_incoming_streams.remove(&stream.id))?;
                         owner
@@ -265,12 +753,19 @@ impl Contract {
                     } else {
                         check_integrity(owner.inactive_outgoing_streams.remove(&stream.id))?;
                         check_integrity(receiver.inactive_incoming_streams.remove(&stream.id))?;
+                        if stream.tokens_awaiting_approval > 0 {
+                            // Tokens approved while the stream was paused
+                            promises.extend(self.process_payment(
+                                stream,
+                                &mut owner,
+                                &mut receiver,
+                                None,
+                            )?);
+                        }
                     }
                     if !stream.status.is_terminated() {
                         // Refund can be requested only if stream is not terminated naturally yet
-                        if let Some(promise) = self.process_refund(stream)? {
-                            promises.push(promise);
-                        }
+                        promises.extend(self.process_refund(stream, &mut receiver, &reason)?);
                         stream.status = StreamStatus::Finished { reason };
                     }
                 }
@@ -278,18 +773,41 @@ impl Contract {
                     // Processed separately
                     unreachable!();
                 }
-                ActionType::Withdraw => {
-                    check_integrity(stream.status == StreamStatus::Active)?;
-                    if let Some(promise) = self.process_payment(stream, &mut receiver)? {
-                        promises.push(promise);
+                ActionType::Dispute { opened_by } => {
+                    if stream.status == StreamStatus::Active {
+                        // Tokens accrued are kept to be split by the arbiter
+                        owner
+                            .total_outgoing
+                            .entry(stream.token_account_id.clone())
+                            .and_modify(|e| *e -= stream.tokens_per_sec);
+                        receiver
+                            .total_incoming
+                            .entry(stream.token_account_id.clone())
+                            .and_modify(|e| *e -= stream.tokens_per_sec);
+                        check_integrity(owner.active_outgoing_streams.remove(&stream.id))?;
+                        check_integrity(receiver.active_incoming_streams.remove(&stream.id))?;
+                        check_integrity(owner.inactive_outgoing_streams.insert(&stream.id))?;
+                        check_integrity(receiver.inactive_incoming_streams.insert(&stream.id))?;
+                        self.stats_dec_active_streams(&stream.token_account_id);
                     }
+                    stream.status = StreamStatus::Disputed { opened_by };
+                }
+                ActionType::Withdraw { keeper_id } => {
+                    check_integrity(stream.status == StreamStatus::Active)?;
+                    promises.extend(self.process_payment(
+                        stream,
+                        &mut owner,
+                        &mut receiver,
+                        keeper_id.as_ref(),
+                    )?);
                     if stream.status.is_terminated() {
-                        check_integrity(
-                            stream.status
-                                == StreamStatus::Finished {
-                                    reason: StreamFinishReason::FinishedNaturally,
-                                },
-                        )?;
+                        check_integrity(matches!(
+                            stream.status,
+                            StreamStatus::Finished {
+                                reason: StreamFinishReason::FinishedNaturally
+                                    | StreamFinishReason::FinishedBecauseTreasuryExhausted,
+                            }
+                        ))?;
                         check_integrity(owner.active_outgoing_streams.remove(&stream.id))?;
                         check_integrity(receiver.active_incoming_streams.remove(&stream.id))?;
                         owner
@@ -306,35 +824,291 @@ impl Contract {
             }
         }
 
+        let is_active = if stream.status.is_terminated() {
+            None
+        } else {
+            Some(stream.status == StreamStatus::Active)
+        };
+        if stream.funded_from_treasury {
+            owner.update_treasury_outflow(stream, was_active, is_active);
+        }
+
         stream.last_action = env::block_timestamp();
         self.save_account(owner)?;
         self.save_account(receiver)?;
+        if is_active.is_none() {
+            // Receivership of terminated stream is not transferable anymore
+            self.burn_stream_nft(stream)?;
+        }
+
+        self.update_token_streams(stream, was_active, is_active)?;
 
         Ok(promises)
     }
 
+    // Moves the stream within the token index.
+    // None stands for the stream that is not indexed.
+    //
+    // Streams created before the index was introduced may be missing there
+    // until `dao_index_token_streams` is called, so removal is not checked.
+    // The outflow is changed only for streams that are actually indexed.
+    fn update_token_streams(
+        &mut self,
+        stream: &Stream,
+        was_active: Option<bool>,
+        is_active: Option<bool>,
+    ) -> Result<(), ContractError> {
+        if was_active == is_active {
+            return Ok(());
+        }
+        let mut token_streams = self
+            .token_streams
+            .get(&stream.token_account_id)
+            .unwrap_or_else(|| TokenStreams::new(&stream.token_account_id));
+        match was_active {
+            Some(true) if token_streams.active_streams.remove(&stream.id) => {
+                token_streams.outflow -= stream.tokens_per_sec;
+            }
+            Some(false) => {
+                token_streams.inactive_streams.remove(&stream.id);
+            }
+            _ => {}
+        }
+        match is_active {
+            Some(true) => {
+                check_integrity(token_streams.active_streams.insert(&stream.id))?;
+                token_streams.outflow += stream.tokens_per_sec;
+            }
+            Some(false) => check_integrity(token_streams.inactive_streams.insert(&stream.id))?,
+            None => {}
+        }
+        self.token_streams
+            .insert(&stream.token_account_id, &token_streams);
+        Ok(())
+    }
+
     fn process_payment(
+        &mut self,
+        stream: &mut Stream,
+        owner: &mut Account,
+        account: &mut Account,
+        keeper_id: Option<&AccountId>,
+    ) -> Result<Vec<Promise>, ContractError> {
+        let token = self.dao.get_token(&stream.token_account_id);
+        let is_treasury_exhausted =
+            if stream.funded_from_treasury && stream.status == StreamStatus::Active {
+                self.draw_from_treasury(stream, owner)
+            } else {
+                false
+            };
+        let (mut payment, commission) = stream.process_withdraw(&token);
+        if is_treasury_exhausted {
+            stream.status = StreamStatus::Finished {
+                reason: StreamFinishReason::FinishedBecauseTreasuryExhausted,
+            };
+        }
+        self.stats_withdraw(&token, payment, commission);
+
+        let mut promises = vec![];
+        if let Some(keeper_id) = keeper_id {
+            // Keepers are tipped by the receiver
+            match account.keeper_tip {
+                Some(KeeperTip::Share { bps }) => {
+                    let tip = payment * bps as Balance / 10_000;
+                    payment -= tip;
+                    promises.extend(self.ft_transfer_from_finance(
+                        token.account_id.clone(),
+                        keeper_id.clone(),
+                        tip,
+                    )?);
+                }
+                Some(KeeperTip::Flat { amount }) if payment > 0 && account.deposit >= amount => {
+                    account.deposit -= amount;
+                    promises.push(Promise::new(keeper_id.clone()).transfer(amount));
+                }
+                _ => {}
+            }
+        }
+
+        promises.extend(self.transfer_payment(stream, account, token.account_id, payment)?);
+        Ok(promises)
+    }
+
+    // Adds the deposit to the balance of the stream, the debt is paid
+    // to the receiver first. The owner is passed by the caller
+    // as it's extracted already while depositing chained streams.
+    pub(crate) fn process_deposit(
+        &mut self,
+        stream: &mut Stream,
+        owner: &mut Account,
+        amount: Balance,
+    ) -> Result<Vec<Promise>, ContractError> {
+        let mut promises = vec![];
+        if stream.debt_at(env::block_timestamp()) > 0 && stream.status == StreamStatus::Active {
+            // Settling the debt, the rest of the balance is paid as well
+            let mut receiver = self.extract_account(&stream.receiver_id)?;
+            promises.extend(self.process_payment(stream, owner, &mut receiver, None)?);
+            self.save_account(receiver)?;
+        }
+        let debt_payment = min(amount, stream.settled_debt);
+        stream.balance += amount - debt_payment;
+
+        self.stats_inc_stream_deposit(&stream.token_account_id.clone(), &amount, &0);
+
+        if debt_payment > 0 {
+            promises.extend(self.process_debt_payment(stream, debt_payment)?);
+        }
+        Ok(promises)
+    }
+
+    // Pays the debt of the stream to the receiver out of the deposit,
+    // the tokens are in finance contract already.
+    pub(crate) fn process_debt_payment(
+        &mut self,
+        stream: &mut Stream,
+        amount: Balance,
+    ) -> Result<Vec<Promise>, ContractError> {
+        stream.settled_debt -= amount;
+        if stream.settled_debt == 0 {
+            stream.insolvent_since = None;
+        }
+        self.process_extra_payment(stream, amount)
+    }
+
+    // Pays the amount beyond the accrued tokens to the receiver
+    // out of the balance or the deposit.
+    pub(crate) fn process_extra_payment(
+        &mut self,
+        stream: &mut Stream,
+        amount: Balance,
+    ) -> Result<Vec<Promise>, ContractError> {
+        let mut receiver = self.extract_account(&stream.receiver_id)?;
+        let promises = self.transfer_extra_payment(stream, &mut receiver, amount)?;
+        self.save_account(receiver)?;
+        Ok(promises)
+    }
+
+    // The commission is taken as for regular payments.
+    fn transfer_extra_payment(
         &mut self,
         stream: &mut Stream,
         account: &mut Account,
-    ) -> Result<Option<Promise>, ContractError> {
+        amount: Balance,
+    ) -> Result<Vec<Promise>, ContractError> {
         let token = self.dao.get_token(&stream.token_account_id);
-        let (payment, commission) = stream.process_withdraw(&token);
+        let (payment, commission) = if token.is_payment {
+            token.apply_commission(amount)
+        } else {
+            (amount, 0)
+        };
+        stream.tokens_total_withdrawn += amount;
+        self.stats_withdraw(&token, payment, commission);
+        self.transfer_payment(stream, account, token.account_id, payment)
+    }
+
+    fn transfer_payment(
+        &mut self,
+        stream: &mut Stream,
+        account: &mut Account,
+        token_account_id: AccountId,
+        mut payment: Balance,
+    ) -> Result<Vec<Promise>, ContractError> {
+        let mut promises = vec![];
+        if let Some(pledge) = stream.pledge.as_mut() {
+            // The lender is repaid first
+            let repayment = min(
+                payment * pledge.share_bps as Balance / 10_000,
+                pledge.amount - pledge.repaid,
+            );
+            pledge.repaid += repayment;
+            payment -= repayment;
+            promises.extend(self.ft_transfer_from_finance(
+                token_account_id.clone(),
+                pledge.lender_id.clone(),
+                repayment,
+            )?);
+            if pledge.repaid == pledge.amount {
+                log!(
+                    "stream {} pledge to {} is repaid",
+                    String::from(&Base58CryptoHash::from(stream.id)),
+                    pledge.lender_id
+                );
+                stream.pledge = None;
+            }
+        }
+
         account
             .total_received
             .entry(stream.token_account_id.clone())
             .and_modify(|e| *e += payment)
             .or_insert(payment);
-        self.stats_withdraw(&token, payment, commission);
-        self.ft_transfer_from_finance(token.account_id, stream.receiver_id.clone(), payment)
+        if let Some(child_stream_id) = stream.child_stream_id {
+            if payment > 0 {
+                if let Some(child_promises) = self.deposit_to_child(
+                    &child_stream_id.into(),
+                    account,
+                    &token_account_id,
+                    payment,
+                )? {
+                    promises.extend(child_promises);
+                    return Ok(promises);
+                }
+            }
+        }
+        promises.extend(self.payout_from_finance(token_account_id, account, payment)?);
+        Ok(promises)
     }
 
-    fn process_refund(&mut self, stream: &mut Stream) -> Result<Option<Promise>, ContractError> {
+    fn process_refund(
+        &mut self,
+        stream: &mut Stream,
+        receiver: &mut Account,
+        reason: &StreamFinishReason,
+    ) -> Result<Vec<Promise>, ContractError> {
+        let mut promises = vec![];
+        if *reason == StreamFinishReason::StoppedByOwner {
+            // Severance is paid before the rest is refunded
+            let severance = stream
+                .cancel_policy
+                .as_ref()
+                .map_or(0, |policy| min(policy.severance, stream.balance));
+            if severance > 0 {
+                stream.balance -= severance;
+                promises.extend(self.transfer_extra_payment(stream, receiver, severance)?);
+            }
+        }
+
         let token = self.dao.get_token(&stream.token_account_id);
         let refund = stream.balance;
         stream.balance = 0;
+        stream.tokens_awaiting_approval = 0;
         self.stats_refund(&token, refund);
-        self.ft_transfer_from_finance(token.account_id, stream.owner_id.clone(), refund)
+        promises.extend(self.ft_transfer_from_finance(
+            token.account_id,
+            stream.owner_id.clone(),
+            refund,
+        )?);
+        Ok(promises)
+    }
+
+    // Adds the stream to the token index if it's not there yet.
+    pub(crate) fn index_token_stream(&mut self, stream: &Stream) {
+        if stream.status.is_terminated() {
+            return;
+        }
+        let mut token_streams = self
+            .token_streams
+            .get(&stream.token_account_id)
+            .unwrap_or_else(|| TokenStreams::new(&stream.token_account_id));
+        if stream.status == StreamStatus::Active {
+            if token_streams.active_streams.insert(&stream.id) {
+                token_streams.outflow += stream.tokens_per_sec;
+            }
+        } else {
+            token_streams.inactive_streams.insert(&stream.id);
+        }
+        self.token_streams
+            .insert(&stream.token_account_id, &token_streams);
     }
 
     pub(crate) fn view_stream(&self, stream_id: &StreamId) -> Result<Stream, ContractError> {
@@ -348,7 +1122,15 @@ impl Contract {
 
     pub(crate) fn extract_stream(&mut self, stream_id: &StreamId) -> Result<Stream, ContractError> {
         match self.streams.remove(stream_id) {
-            Some(vstream) => Ok(vstream.into()),
+            Some(vstream) => {
+                // Legacy streams are not counted until saved
+                let is_counted = matches!(vstream, VStream::Current(_));
+                let stream: Stream = vstream.into();
+                if is_counted {
+                    self.stream_status_counts.dec(&stream.status);
+                }
+                Ok(stream)
+            }
             None => Err(ContractError::StreamNotExist {
                 stream_id: *stream_id,
             }),
@@ -356,9 +1138,23 @@ impl Contract {
     }
 
     pub(crate) fn save_stream(&mut self, stream: Stream) -> Result<(), ContractError> {
+        self.stream_status_counts.inc(&stream.status);
         match self.streams.insert(&stream.id.clone(), &stream.into()) {
             None => Ok(()),
             Some(_) => Err(ContractError::DataCorruption),
         }
     }
+
+    // Rewrites streams stored before the upgrade in place,
+    // so the order of streams is kept between pages.
+    pub(crate) fn migrate_streams_op(&mut self, from: u32, limit: u32) {
+        for i in from..min(self.streams.len() as _, from.saturating_add(limit)) {
+            let vstream = self.streams.values_as_vector().get(i as _).unwrap();
+            if let VStream::V0(_) = vstream {
+                let stream: Stream = vstream.into();
+                self.stream_status_counts.inc(&stream.status);
+                self.streams.insert(&stream.id.clone(), &stream.into());
+            }
+        }
+    }
 }
@@ -1,23 +1,124 @@
 use crate::*;
 
 impl Contract {
+    // The single stream takes the whole transfer, `balance` of the request
+    // is used by batches only.
     pub(crate) fn create_stream_op(
         &mut self,
-        description: Option<String>,
         creator_id: AccountId,
-        owner_id: AccountId,
-        receiver_id: AccountId,
         token_account_id: AccountId,
         initial_balance: Balance,
-        tokens_per_sec: Balance,
-        cliff_period_sec: Option<u32>,
-        is_auto_start_enabled: Option<bool>,
-        is_expirable: Option<bool>,
-        is_locked: Option<bool>,
+        request: CreateRequest,
     ) -> Result<(), ContractError> {
         // NEP-141 forbids zero-token transfers, so this should never happen.
         assert_ne!(initial_balance, 0);
 
+        let (_, balance, is_storage_charged) = self.create_stream(
+            creator_id,
+            token_account_id.clone(),
+            initial_balance,
+            request,
+        )?;
+
+        self.ft_transfer_from_self(token_account_id, self.finance_id.clone(), balance)?;
+        if !is_storage_charged {
+            self.storage_needs_transfer(1);
+        }
+
+        Ok(())
+    }
+
+    // All streams of the batch are created or none of them,
+    // as any error reverts the whole transfer call.
+    pub(crate) fn create_streams_op(
+        &mut self,
+        creator_id: AccountId,
+        token_account_id: AccountId,
+        amount: Balance,
+        requests: Vec<CreateRequest>,
+    ) -> Result<Vec<StreamId>, ContractError> {
+        let mut requested: Balance = 0;
+        for request in requests.iter() {
+            let balance: Balance = request.balance.map_or(0, |balance| balance.into());
+            if balance == 0 {
+                return Err(ContractError::ZeroTokenTransfer);
+            }
+            requested = requested.saturating_add(balance);
+        }
+        if requested != amount {
+            return Err(ContractError::InvalidBatchBalance {
+                expected: amount,
+                received: requested,
+            });
+        }
+
+        let mut stream_ids = vec![];
+        let mut total_balance = 0;
+        let mut storage_needs_streams = 0;
+        for request in requests {
+            let balance = request.balance.unwrap().into();
+            let (stream_id, balance, is_storage_charged) = self.create_stream(
+                creator_id.clone(),
+                token_account_id.clone(),
+                balance,
+                request,
+            )?;
+            stream_ids.push(stream_id);
+            total_balance += balance;
+            if !is_storage_charged {
+                storage_needs_streams += 1;
+            }
+        }
+
+        self.ft_transfer_from_self(token_account_id, self.finance_id.clone(), total_balance)?;
+        if storage_needs_streams > 0 {
+            self.storage_needs_transfer(storage_needs_streams);
+        }
+
+        Ok(stream_ids)
+    }
+
+    fn storage_needs_transfer(&self, streams: u32) {
+        // Covering storage needs from finance contract
+        ext_finance::ext(self.finance_id.clone())
+            .with_attached_deposit(ONE_YOCTO)
+            .with_static_gas(Gas::ONE_TERA * 10)
+            .streaming_storage_needs_transfer(Some(streams));
+    }
+
+    // Creates the stream with no transfers made.
+    //
+    // Returns the balance to be transferred to finance contract
+    // and whether the storage is charged to the creator.
+    //
+    // For streams funded from treasury the balance is deposited
+    // to the owner's treasury instead.
+    fn create_stream(
+        &mut self,
+        creator_id: AccountId,
+        token_account_id: AccountId,
+        initial_balance: Balance,
+        request: CreateRequest,
+    ) -> Result<(StreamId, Balance, bool), ContractError> {
+        let CreateRequest {
+            description,
+            owner_id,
+            receiver_id,
+            tokens_per_sec,
+            cliff_period_sec,
+            is_auto_start_enabled,
+            is_expirable,
+            is_locked,
+            funded_from_treasury,
+            approver_id,
+            milestones,
+            cancel_policy,
+            is_clawback_allowed,
+            arbiter_id,
+            ..
+        } = request;
+        let tokens_per_sec: Balance = tokens_per_sec.into();
+
         if description.is_some() && description.clone().unwrap().len() >= MAX_DESCRIPTION_LEN {
             return Err(ContractError::DescriptionTooLong {
                 max_description_len: MAX_DESCRIPTION_LEN,
@@ -43,10 +144,72 @@ impl Contract {
             Some(value) => value,
             None => false,
         };
+        let funded_from_treasury = funded_from_treasury.unwrap_or(false);
+        if funded_from_treasury {
+            // Nobody else may draw from the owner's treasury
+            if owner_id != creator_id {
+                return Err(ContractError::CallerIsNotStreamOwner {
+                    expected: owner_id,
+                    received: creator_id,
+                });
+            }
+            // The treasury may be withdrawn at any time
+            if is_locked {
+                return Err(ContractError::CannotLockTreasuryStream);
+            }
+            // Tokens awaiting approval cannot be kept after exhaustion
+            if approver_id.is_some() {
+                return Err(ContractError::CannotGateTreasuryStream);
+            }
+        }
+        if let Some(policy) = &cancel_policy {
+            // Locked streams cannot be stopped, treasury streams have no balance
+            if is_locked || funded_from_treasury || policy.severance > MAX_AMOUNT {
+                return Err(ContractError::InvalidCancelPolicy);
+            }
+        }
+        let is_clawback_allowed = is_clawback_allowed.unwrap_or(false);
+        // The treasury may be withdrawn by the owner at any time
+        if is_clawback_allowed && funded_from_treasury {
+            return Err(ContractError::CannotClawbackTreasuryStream);
+        }
+        if let Some(arbiter_id) = &arbiter_id {
+            // Locked streams cannot be disputed, treasury streams have no balance
+            if *arbiter_id == owner_id
+                || *arbiter_id == receiver_id
+                || is_locked
+                || funded_from_treasury
+            {
+                return Err(ContractError::InvalidArbiter);
+            }
+        }
+        let milestones: Vec<Balance> = milestones
+            .unwrap_or_default()
+            .into_iter()
+            .map(|milestone| milestone.into())
+            .collect();
+        if (!milestones.is_empty() && approver_id.is_none())
+            || milestones.len() > MAX_MILESTONES
+            || milestones.first() == Some(&0)
+            || milestones.windows(2).any(|pair| pair[0] >= pair[1])
+        {
+            return Err(ContractError::InvalidMilestones {
+                max_milestones: MAX_MILESTONES,
+            });
+        }
 
         self.create_account_if_not_exist(&creator_id)?;
+        // Accounts created for the owner and the receiver
+        // are charged to the creator as well.
+        let storage_usage_accounts = env::storage_usage();
         self.create_account_if_not_exist(&owner_id)?;
         self.create_account_if_not_exist(&receiver_id)?;
+        let storage_usage_accounts = env::storage_usage() - storage_usage_accounts;
+        let is_pending = self.view_account(&receiver_id, true)?.require_accept;
+
+        // Everything stored from now is charged to the creator
+        // as the storage used by the stream.
+        let storage_usage_initial = env::storage_usage();
 
         let mut creator = self.extract_account(&creator_id)?;
         let mut balance = initial_balance;
@@ -66,10 +229,10 @@ impl Contract {
             commission += token.commission_on_create;
 
             if is_auto_start_enabled {
-                if balance == 0 {
+                if balance == 0 && !funded_from_treasury {
                     return Err(ContractError::ZeroBalanceStreamStart);
                 }
-                if is_locked {
+                if is_locked && !is_pending {
                     // For locked streams we take all commission when the stream is started
                     let (_, calculated_commission) = token.apply_commission(balance);
                     commission += calculated_commission;
@@ -100,8 +263,22 @@ impl Contract {
             None
         };
 
+        let stream_balance = if funded_from_treasury {
+            creator.deposit_to_treasury(&token_account_id, balance);
+            if is_auto_start_enabled && !creator.view_treasury(&token_account_id).is_solvent() {
+                return Err(ContractError::ZeroBalanceStreamStart);
+            }
+            0
+        } else {
+            balance
+        };
+
         // Validations passed
 
+        if funded_from_treasury {
+            self.update_token_treasury_balance(&token_account_id, balance, 0);
+        }
+
         let mut stream = Stream::new(
             self.streams.len(),
             description,
@@ -109,12 +286,18 @@ impl Contract {
             owner_id,
             receiver_id,
             token_account_id,
-            balance,
+            stream_balance,
             tokens_per_sec,
             cliff,
             is_expirable,
             is_locked,
         );
+        stream.funded_from_treasury = funded_from_treasury;
+        stream.approver_id = approver_id;
+        stream.milestones = milestones;
+        stream.cancel_policy = cancel_policy;
+        stream.is_clawback_allowed = is_clawback_allowed;
+        stream.arbiter_id = arbiter_id;
 
         creator.total_streams_created += 1;
         creator.last_created_stream = Some(stream.id);
@@ -122,32 +305,34 @@ impl Contract {
 
         self.process_action(&mut stream, ActionType::Init)?;
 
-        self.stats_inc_stream_deposit(&stream.token_account_id, &balance, &commission);
+        self.stats_inc_stream_deposit(&stream.token_account_id, &stream_balance, &commission);
         self.stats_inc_streams(
             &stream.token_account_id,
             is_aurora_address(&stream.owner_id) | is_aurora_address(&stream.receiver_id),
             token.is_payment,
         );
 
-        if is_auto_start_enabled {
+        if is_pending {
+            // The stream is started if needed once accepted
+            stream.status = StreamStatus::Pending {
+                is_auto_start_enabled,
+                commission: if token.is_payment {
+                    commission
+                } else {
+                    self.dao.commission_non_payment_ft
+                },
+            };
+        } else if is_auto_start_enabled {
             self.process_action(&mut stream, ActionType::Start)?;
         }
 
-        self.ft_transfer_from_self(
-            stream.token_account_id.clone(),
-            self.finance_id.clone(),
-            stream.balance,
-        )?;
-
-        // Covering storage needs from finance contract
-        ext_finance::ext(self.finance_id.clone())
-            .with_attached_deposit(ONE_YOCTO)
-            .with_static_gas(Gas::ONE_TERA * 10)
-            .streaming_storage_needs_transfer();
-
+        let stream_id = stream.id;
         self.save_stream(stream)?;
 
-        Ok(())
+        let is_storage_charged =
+            self.charge_stream_storage(&stream_id, storage_usage_initial, storage_usage_accounts)?;
+
+        Ok((stream_id, balance, is_storage_charged))
     }
 
     pub(crate) fn deposit_op(
@@ -161,67 +346,146 @@ impl Contract {
 
         let stream_id = stream_id.into();
         let mut stream = self.extract_stream(&stream_id)?;
-        if stream.status.is_terminated() {
-            return Err(ContractError::StreamTerminated { stream_id });
+        match check_stream_deposit(&mut stream, &token_account_id, amount) {
+            Err(ContractError::StreamExpired { stream_id }) => {
+                let action = self.process_action(
+                    &mut stream,
+                    ActionType::Stop {
+                        reason: StreamFinishReason::FinishedBecauseCannotBeExtended,
+                    },
+                )?;
+                assert!(action.is_empty());
+                self.save_stream(stream)?;
+                return Err(ContractError::StreamExpired { stream_id });
+            }
+            result => result?,
         }
 
-        if stream.is_locked && stream.status != StreamStatus::Initialized {
-            return Err(ContractError::StreamLocked {
-                stream_id: stream.id,
-            });
-        }
+        // Validations passed
 
-        stream.update_cliff();
+        self.ft_transfer_from_self(
+            stream.token_account_id.clone(),
+            self.finance_id.clone(),
+            amount,
+        )?;
 
-        if stream.cliff.is_some() {
-            return Err(ContractError::CliffNotPassed {
-                timestamp: stream.cliff.unwrap(),
-            });
-        }
+        let mut owner = self.extract_account(&stream.owner_id)?;
+        self.process_deposit(&mut stream, &mut owner, amount)?;
+        self.save_account(owner)?;
 
-        if stream.available_to_withdraw() == stream.balance
-            && stream.balance > 0
-            && stream.is_expirable
-        {
-            let action = self.process_action(
-                &mut stream,
-                ActionType::Stop {
-                    reason: StreamFinishReason::FinishedBecauseCannotBeExtended,
-                },
-            )?;
-            assert!(action.is_empty());
-            self.save_stream(stream)?;
-            return Err(ContractError::StreamExpired { stream_id });
-        }
+        self.save_stream(stream)?;
 
-        if stream.token_account_id != token_account_id {
-            return Err(ContractError::InvalidToken {
-                expected: stream.token_account_id,
-                received: token_account_id,
-            });
-        }
+        Ok(())
+    }
 
-        if amount > MAX_AMOUNT || stream.balance + amount > MAX_AMOUNT {
-            return Err(ContractError::ExceededMaxBalance {
-                max_amount: MAX_AMOUNT,
-            });
+    // Deposits the payment of the parent stream, the tokens are in finance contract already.
+    // None is returned if the child cannot be deposited, expired child is left
+    // to be stopped on its next action. The owner of the child is the receiver
+    // of the parent, extracted while the payment is processed.
+    pub(crate) fn deposit_to_child(
+        &mut self,
+        child_stream_id: &StreamId,
+        owner: &mut Account,
+        token_account_id: &AccountId,
+        amount: Balance,
+    ) -> Result<Option<Vec<Promise>>, ContractError> {
+        let mut child = match self.view_stream(child_stream_id) {
+            Ok(child) => child,
+            Err(_) => return Ok(None),
+        };
+        if child.owner_id != owner.id
+            || check_stream_deposit(&mut child, token_account_id, amount).is_err()
+        {
+            return Ok(None);
+        }
+        // The debt is paid to the receiver, which may be extracted as well,
+        // e.g. being the owner of the parent
+        if child.debt_at(env::block_timestamp()) > 0
+            && self.view_account(&child.receiver_id, true).is_err()
+        {
+            return Ok(None);
         }
 
         // Validations passed
+        self.extract_stream(child_stream_id)?;
 
-        stream.balance += amount;
+        let promises = self.process_deposit(&mut child, owner, amount)?;
+        self.save_stream(child)?;
 
-        self.ft_transfer_from_self(
-            stream.token_account_id.clone(),
-            self.finance_id.clone(),
-            amount,
-        )?;
+        Ok(Some(promises))
+    }
 
-        self.save_stream(stream)?;
+    // Sets or removes the child stream of the stream, see `Stream::child_stream_id`.
+    pub fn chain_stream_op(
+        &mut self,
+        sender_id: &AccountId,
+        stream_id: CryptoHash,
+        child_stream_id: Option<CryptoHash>,
+    ) -> Result<(), ContractError> {
+        let mut stream = self.view_stream(&stream_id)?;
+
+        if stream.receiver_id != *sender_id {
+            return Err(ContractError::CallerIsNotStreamReceiver {
+                expected: stream.receiver_id,
+                received: sender_id.clone(),
+            });
+        }
+        if stream.status.is_terminated() {
+            return Err(ContractError::StreamTerminated { stream_id });
+        }
+        let child = match child_stream_id {
+            Some(child_stream_id) => {
+                let child = self.view_stream(&child_stream_id)?;
+                if child.owner_id != *sender_id {
+                    return Err(ContractError::CallerIsNotStreamOwner {
+                        expected: child.owner_id,
+                        received: sender_id.clone(),
+                    });
+                }
+                if child.status.is_terminated() {
+                    return Err(ContractError::StreamTerminated {
+                        stream_id: child_stream_id,
+                    });
+                }
+                if child.token_account_id != stream.token_account_id {
+                    return Err(ContractError::InvalidToken {
+                        expected: stream.token_account_id,
+                        received: child.token_account_id,
+                    });
+                }
+                if child.funded_from_treasury {
+                    return Err(ContractError::StreamFundedFromTreasury {
+                        stream_id: child_stream_id,
+                    });
+                }
+                if child_stream_id == stream_id || child.parent_stream_id.is_some() {
+                    return Err(ContractError::CannotChainStream {
+                        stream_id: child_stream_id,
+                    });
+                }
+                Some(child)
+            }
+            None => None,
+        };
 
-        self.stats_inc_stream_deposit(&token_account_id, &amount, &0);
+        // Validations passed
+        self.extract_stream(&stream_id)?;
 
-        Ok(())
+        if let Some(prev_child_stream_id) = stream.child_stream_id {
+            // The previous child may be pruned already
+            if let Ok(mut prev_child) = self.extract_stream(&prev_child_stream_id.into()) {
+                prev_child.parent_stream_id = None;
+                self.save_stream(prev_child)?;
+            }
+        }
+        if let Some(mut child) = child {
+            self.extract_stream(&child.id)?;
+            child.parent_stream_id = Some(stream_id.into());
+            self.save_stream(child)?;
+        }
+        stream.child_stream_id = child_stream_id.map(|child_stream_id| child_stream_id.into());
+
+        self.save_stream(stream)
     }
 
     pub fn start_stream_op(
@@ -229,31 +493,60 @@ impl Contract {
         sender_id: &AccountId,
         stream_id: CryptoHash,
     ) -> Result<(), ContractError> {
-        let mut stream = self.extract_stream(&stream_id)?;
-
-        if stream.is_locked && stream.status != StreamStatus::Initialized {
+        // Validations are made on the view of the stream,
+        // so the stream is kept untouched if any of them fails.
+        let mut stream = self.view_stream(&stream_id)?;
+
+        if stream.is_locked
+            && !matches!(
+                stream.status,
+                StreamStatus::Initialized | StreamStatus::Pending { .. }
+            )
+        {
             return Err(ContractError::StreamLocked {
                 stream_id: stream.id,
             });
         }
 
-        if stream.owner_id != *sender_id {
+        if stream.owner_id != *sender_id
+            && !self.is_operator(&stream.owner_id, sender_id, OperatorAction::Start, &stream)
+        {
             return Err(ContractError::CallerIsNotStreamOwner {
                 expected: stream.owner_id,
                 received: sender_id.clone(),
             });
         }
+        stream.check_not_frozen()?;
         if stream.status != StreamStatus::Paused && stream.status != StreamStatus::Initialized {
             return Err(ContractError::CannotStartStream {
                 stream_status: stream.status,
             });
         }
-        if stream.balance == 0 {
-            return Err(ContractError::ZeroBalanceStreamStart);
-        }
+        self.check_start_balance(&stream)?;
 
         // Validations passed
+        self.extract_stream(&stream_id)?;
+
+        self.process_start(&mut stream)?;
+
+        self.save_stream(stream)?;
+
+        Ok(())
+    }
 
+    fn check_start_balance(&self, stream: &Stream) -> Result<(), ContractError> {
+        if stream.funded_from_treasury {
+            let owner = self.view_account(&stream.owner_id, true)?;
+            if !owner.view_treasury(&stream.token_account_id).is_solvent() {
+                return Err(ContractError::ZeroBalanceStreamStart);
+            }
+        } else if stream.balance == 0 {
+            return Err(ContractError::ZeroBalanceStreamStart);
+        }
+        Ok(())
+    }
+
+    fn process_start(&mut self, stream: &mut Stream) -> Result<(), ContractError> {
         if stream.is_locked {
             let token = self.dao.get_token(&stream.token_account_id);
             if token.is_payment {
@@ -263,12 +556,100 @@ impl Contract {
             }
         };
 
-        assert!(self
-            .process_action(&mut stream, ActionType::Start)?
-            .is_empty());
+        assert!(self.process_action(stream, ActionType::Start)?.is_empty());
+
+        Ok(())
+    }
+
+    pub fn accept_stream_op(
+        &mut self,
+        sender_id: &AccountId,
+        stream_id: CryptoHash,
+    ) -> Result<(), ContractError> {
+        let mut stream = self.view_stream(&stream_id)?;
+        self.check_pending_receiver(sender_id, &stream)?;
+
+        // Validations passed
+        self.extract_stream(&stream_id)?;
+
+        let is_auto_start_enabled = matches!(
+            stream.status,
+            StreamStatus::Pending {
+                is_auto_start_enabled: true,
+                ..
+            }
+        );
+        stream.status = StreamStatus::Initialized;
+        // The owner may start the stream later if it cannot be started now
+        if is_auto_start_enabled && self.check_start_balance(&stream).is_ok() {
+            self.process_start(&mut stream)?;
+        }
+
+        self.save_stream(stream)
+    }
+
+    pub fn reject_stream_op(
+        &mut self,
+        sender_id: &AccountId,
+        stream_id: CryptoHash,
+    ) -> Result<Vec<Promise>, ContractError> {
+        let mut stream = self.view_stream(&stream_id)?;
+        self.check_pending_receiver(sender_id, &stream)?;
+
+        // Validations passed
+        self.extract_stream(&stream_id)?;
+
+        let commission = match stream.status {
+            StreamStatus::Pending { commission, .. } => commission,
+            _ => unreachable!(),
+        };
+        let mut promises = self.process_action(
+            &mut stream,
+            ActionType::Stop {
+                reason: StreamFinishReason::RejectedByReceiver,
+            },
+        )?;
+
+        // The commission is refunded to the creator who has paid it
+        if self.refund_commission_on_reject {
+            let token = self.dao.get_token(&stream.token_account_id);
+            if token.is_payment {
+                self.stats_refund_commission(&token.account_id, commission);
+                if let Some(promise) = self.ft_transfer_from_self(
+                    token.account_id,
+                    stream.creator_id.clone(),
+                    commission,
+                )? {
+                    promises.push(promise);
+                }
+            } else {
+                let mut creator = self.extract_account(&stream.creator_id)?;
+                creator.deposit += commission;
+                self.save_account(creator)?;
+            }
+        }
 
         self.save_stream(stream)?;
 
+        Ok(promises)
+    }
+
+    fn check_pending_receiver(
+        &self,
+        sender_id: &AccountId,
+        stream: &Stream,
+    ) -> Result<(), ContractError> {
+        if stream.receiver_id != *sender_id {
+            return Err(ContractError::CallerIsNotStreamReceiver {
+                expected: stream.receiver_id.clone(),
+                received: sender_id.clone(),
+            });
+        }
+        if !matches!(stream.status, StreamStatus::Pending { .. }) {
+            return Err(ContractError::StreamNotPending {
+                stream_status: stream.status.clone(),
+            });
+        }
         Ok(())
     }
 
@@ -277,7 +658,9 @@ impl Contract {
         sender_id: &AccountId,
         stream_id: CryptoHash,
     ) -> Result<Vec<Promise>, ContractError> {
-        let mut stream = self.extract_stream(&stream_id)?;
+        // Validations are made on the view of the stream,
+        // so the stream is kept untouched if any of them fails.
+        let mut stream = self.view_stream(&stream_id)?;
 
         if stream.is_locked {
             return Err(ContractError::StreamLocked {
@@ -285,18 +668,22 @@ impl Contract {
             });
         }
 
-        if stream.owner_id != *sender_id && stream.receiver_id != *sender_id {
+        if !self.is_stream_actor(sender_id, OperatorAction::Pause, &stream) {
             return Err(ContractError::CallerIsNotStreamActor {
                 owner: stream.owner_id,
                 receiver: stream.receiver_id,
                 caller: sender_id.clone(),
             });
         }
+        stream.check_not_frozen()?;
         if stream.status != StreamStatus::Active {
             return Err(ContractError::CannotPauseStream {
                 stream_status: stream.status,
             });
         }
+        if let Some(stop_at) = stream.stop_at {
+            return Err(ContractError::StopScheduled { stop_at });
+        }
 
         stream.update_cliff();
 
@@ -307,6 +694,7 @@ impl Contract {
         }
 
         // Validations passed
+        self.extract_stream(&stream_id)?;
 
         let promises = self.process_action(&mut stream, ActionType::Pause)?;
 
@@ -320,36 +708,67 @@ impl Contract {
         sender_id: &AccountId,
         stream_id: CryptoHash,
     ) -> Result<Vec<Promise>, ContractError> {
-        let mut stream = self.extract_stream(&stream_id)?;
-
-        if stream.is_locked && stream.status != StreamStatus::Initialized {
+        // Validations are made on the view of the stream,
+        // so the stream is kept untouched if any of them fails.
+        let mut stream = self.view_stream(&stream_id)?;
+
+        if stream.is_locked
+            && !matches!(
+                stream.status,
+                StreamStatus::Initialized | StreamStatus::Pending { .. }
+            )
+        {
             return Err(ContractError::StreamLocked {
                 stream_id: stream.id,
             });
         }
 
-        if stream.owner_id != *sender_id && stream.receiver_id != *sender_id {
+        if !self.is_stream_actor(sender_id, OperatorAction::Stop, &stream) {
             return Err(ContractError::CallerIsNotStreamActor {
                 owner: stream.owner_id,
                 receiver: stream.receiver_id,
                 caller: sender_id.clone(),
             });
         }
-        if stream.status.is_terminated() {
+        stream.check_not_frozen()?;
+        if stream.status.is_terminated() || matches!(stream.status, StreamStatus::Disputed { .. }) {
             return Err(ContractError::CannotStopStream {
                 stream_status: stream.status,
             });
         }
 
-        let reason = if stream.owner_id == *sender_id {
+        // Operators of the owner stop the stream on behalf of the owner
+        let is_owner = stream.owner_id == *sender_id
+            || self.is_operator(&stream.owner_id, sender_id, OperatorAction::Stop, &stream);
+        // The stop scheduled by the owner is finished by anyone
+        let reason = if is_owner || stream.is_stop_due() {
             StreamFinishReason::StoppedByOwner
         } else {
             StreamFinishReason::StoppedByReceiver
         };
 
+        if reason == StreamFinishReason::StoppedByReceiver && stream.pledge.is_some() {
+            return Err(ContractError::StreamPledged { stream_id });
+        }
+
+        let notice_period_sec = if is_owner && !stream.is_stop_due() {
+            self.check_cancel_policy(&stream)?
+        } else {
+            None
+        };
+
         stream.update_cliff();
 
         // Validations passed
+        self.extract_stream(&stream_id)?;
+
+        if let Some(notice_period_sec) = notice_period_sec {
+            // The stream keeps streaming until the notice period passes
+            stream.stop_at =
+                Some(env::block_timestamp() + TICKS_PER_SECOND * notice_period_sec as u64);
+            self.save_stream(stream)?;
+            return Ok(vec![]);
+        }
 
         let promises = self.process_action(&mut stream, ActionType::Stop { reason })?;
 
@@ -358,42 +777,764 @@ impl Contract {
         Ok(promises)
     }
 
-    pub fn withdraw_op(
+    // The new stream takes the share of the remaining balance and the speed,
+    // so the total for the owner and the receiver stays the same.
+    pub fn split_stream_op(
         &mut self,
         sender_id: &AccountId,
         stream_id: CryptoHash,
-    ) -> Result<Vec<Promise>, ContractError> {
-        let mut stream = self.extract_stream(&stream_id)?;
-
-        let receiver_view = self.view_account(&stream.receiver_id, true)?;
+        ratio_bps: u16,
+    ) -> Result<StreamId, ContractError> {
+        let mut stream = self.view_stream(&stream_id)?;
 
-        if receiver_view.id != *sender_id && !receiver_view.is_cron_allowed {
-            return Err(ContractError::CronCallsForbidden {
-                received: receiver_view.id,
+        if stream.owner_id != *sender_id && stream.receiver_id != *sender_id {
+            return Err(ContractError::CallerIsNotStreamActor {
+                owner: stream.owner_id,
+                receiver: stream.receiver_id,
+                caller: sender_id.clone(),
             });
         }
-
-        if stream.status != StreamStatus::Active {
-            return Err(ContractError::CannotWithdraw {
-                stream_status: stream.status,
-            });
+        // The lender's share is taken from the payments of the stream
+        if stream.pledge.is_some() {
+            return Err(ContractError::StreamPledged { stream_id });
         }
-
         stream.update_cliff();
-
-        if stream.cliff.is_some() {
-            return Err(ContractError::CliffNotPassed {
-                timestamp: stream.cliff.unwrap(),
-            });
+        if !stream.is_divisible() {
+            return Err(ContractError::CannotSplitStream { stream_id });
+        }
+        if ratio_bps == 0 || ratio_bps >= 10_000 {
+            return Err(ContractError::InvalidSplitRatio);
+        }
+        let balance = stream.remaining_balance() * ratio_bps as Balance / 10_000;
+        let tokens_per_sec = stream.tokens_per_sec * ratio_bps as Balance / 10_000;
+        if balance == 0 || tokens_per_sec == 0 {
+            return Err(ContractError::InvalidSplitRatio);
         }
 
         // Validations passed
 
-        let promises = self.process_action(&mut stream, ActionType::Withdraw)?;
-
-        self.save_stream(stream)?;
-
-        Ok(promises)
+        let mut split = Stream::new(
+            self.streams.len(),
+            stream.description.clone(),
+            sender_id.clone(),
+            stream.owner_id.clone(),
+            stream.receiver_id.clone(),
+            stream.token_account_id.clone(),
+            balance,
+            tokens_per_sec,
+            None,
+            stream.is_expirable,
+            false,
+        );
+        split.is_clawback_allowed = stream.is_clawback_allowed;
+        split.arbiter_id = stream.arbiter_id.clone();
+
+        self.extract_stream(&stream_id)?;
+
+        let status = stream.status.clone();
+        let is_active = status == StreamStatus::Active;
+        if is_active {
+            // Tokens accrued are paid at the current speed
+            self.process_action(&mut stream, ActionType::Pause)?;
+        }
+        stream.balance -= balance;
+        stream.tokens_per_sec -= tokens_per_sec;
+        if is_active {
+            self.process_action(&mut stream, ActionType::Start)?;
+        }
+        self.save_stream(stream)?;
+
+        // Everything stored from now is charged to the sender
+        // as the storage used by the stream.
+        let storage_usage_initial = env::storage_usage();
+
+        let mut creator = self.extract_account(sender_id)?;
+        creator.total_streams_created += 1;
+        creator.last_created_stream = Some(split.id);
+        self.save_account(creator)?;
+
+        self.process_action(&mut split, ActionType::Init)?;
+        let token = self.dao.get_token(&split.token_account_id);
+        self.stats_inc_streams(
+            &split.token_account_id,
+            is_aurora_address(&split.owner_id) | is_aurora_address(&split.receiver_id),
+            token.is_payment,
+        );
+        if is_active {
+            self.process_action(&mut split, ActionType::Start)?;
+        } else {
+            // Inactive streams are kept in the same sets,
+            // so the status of the paused stream is just copied
+            split.status = status;
+        }
+
+        let split_id = split.id;
+        self.save_stream(split)?;
+
+        if !self.charge_stream_storage(&split_id, storage_usage_initial, 0)? {
+            self.storage_needs_transfer(1);
+        }
+
+        Ok(split_id)
+    }
+
+    // Streams are merged into the first one, the rest are finished.
+    // Streams must have the same owner, receiver, token and status.
+    pub fn merge_streams_op(
+        &mut self,
+        sender_id: &AccountId,
+        stream_ids: Vec<CryptoHash>,
+    ) -> Result<Vec<Promise>, ContractError> {
+        if stream_ids.len() < 2 || stream_ids.len() > MAX_MERGED_STREAMS {
+            return Err(ContractError::InvalidMergedStreams {
+                max_merged_streams: MAX_MERGED_STREAMS,
+            });
+        }
+
+        let mut streams: Vec<Stream> = vec![];
+        let mut balance: Balance = 0;
+        let mut tokens_per_sec: Balance = 0;
+        for stream_id in stream_ids {
+            let mut stream = self.view_stream(&stream_id)?;
+            if stream.owner_id != *sender_id {
+                return Err(ContractError::CallerIsNotStreamOwner {
+                    expected: stream.owner_id,
+                    received: sender_id.clone(),
+                });
+            }
+            if stream.pledge.is_some() {
+                return Err(ContractError::StreamPledged { stream_id });
+            }
+            stream.update_cliff();
+            // Merged streams must share the terms of the target
+            let is_compatible = streams.first().is_none_or(|target| {
+                target.receiver_id == stream.receiver_id
+                    && target.token_account_id == stream.token_account_id
+                    && target.status == stream.status
+                    && target.is_expirable == stream.is_expirable
+                    && target.is_clawback_allowed == stream.is_clawback_allowed
+                    && target.arbiter_id == stream.arbiter_id
+                    && target.cancel_policy == stream.cancel_policy
+                    && target.approver_id == stream.approver_id
+                    && target.milestones == stream.milestones
+            });
+            // Links of chained streams would point to the finished ones
+            let is_chained = stream.child_stream_id.is_some() || stream.parent_stream_id.is_some();
+            if !stream.is_divisible()
+                || !is_compatible
+                || is_chained
+                || streams.iter().any(|merged| merged.id == stream_id)
+            {
+                return Err(ContractError::CannotMergeStreams { stream_id });
+            }
+            balance = balance.saturating_add(stream.remaining_balance());
+            tokens_per_sec = tokens_per_sec.saturating_add(stream.tokens_per_sec);
+            streams.push(stream);
+        }
+        if balance > MAX_AMOUNT {
+            return Err(ContractError::ExceededMaxBalance {
+                max_amount: MAX_AMOUNT,
+            });
+        }
+        if tokens_per_sec > MAX_STREAMING_SPEED {
+            return Err(ContractError::InvalidStreamingSpeed {
+                min_streaming_speed: MIN_STREAMING_SPEED,
+                max_streaming_speed: MAX_STREAMING_SPEED,
+                received: tokens_per_sec,
+            });
+        }
+
+        // Validations passed
+
+        let mut promises = vec![];
+        let is_active = streams[0].status == StreamStatus::Active;
+        for stream in streams.iter_mut() {
+            self.extract_stream(&stream.id)?;
+            if is_active {
+                // Tokens accrued are paid at the current speed
+                promises.extend(self.process_action(stream, ActionType::Pause)?);
+            }
+        }
+        let (target, merged) = streams.split_first_mut().unwrap();
+        for stream in merged.iter_mut() {
+            target.balance += stream.balance;
+            target.tokens_per_sec += stream.tokens_per_sec;
+            // Nothing is left to refund
+            stream.balance = 0;
+            promises.extend(self.process_action(
+                stream,
+                ActionType::Stop {
+                    reason: StreamFinishReason::MergedIntoStream,
+                },
+            )?);
+        }
+        if is_active {
+            self.process_action(target, ActionType::Start)?;
+        }
+        for stream in streams {
+            self.save_stream(stream)?;
+        }
+
+        Ok(promises)
+    }
+
+    // Disputes stop accrual and withdrawals until resolved by the arbiter.
+    pub fn open_dispute_op(
+        &mut self,
+        sender_id: &AccountId,
+        stream_id: CryptoHash,
+    ) -> Result<(), ContractError> {
+        let mut stream = self.view_stream(&stream_id)?;
+
+        if stream.owner_id != *sender_id && stream.receiver_id != *sender_id {
+            return Err(ContractError::CallerIsNotStreamActor {
+                owner: stream.owner_id,
+                receiver: stream.receiver_id,
+                caller: sender_id.clone(),
+            });
+        }
+        if stream.arbiter_id.is_none() {
+            return Err(ContractError::NoArbiter { stream_id });
+        }
+        if !matches!(
+            stream.status,
+            StreamStatus::Initialized | StreamStatus::Active | StreamStatus::Paused
+        ) {
+            return Err(ContractError::CannotOpenDispute {
+                stream_status: stream.status,
+            });
+        }
+        stream.check_not_frozen()?;
+
+        // Validations passed
+        self.extract_stream(&stream_id)?;
+
+        let action = self.process_action(
+            &mut stream,
+            ActionType::Dispute {
+                opened_by: sender_id.clone(),
+            },
+        )?;
+        assert!(action.is_empty());
+
+        self.save_stream(stream)
+    }
+
+    // The receiver is paid the amount out of the balance
+    // and the rest is refunded to the owner.
+    pub fn resolve_dispute_op(
+        &mut self,
+        sender_id: &AccountId,
+        stream_id: CryptoHash,
+        receiver_amount: Balance,
+    ) -> Result<Vec<Promise>, ContractError> {
+        let mut stream = self.view_stream(&stream_id)?;
+
+        if stream.arbiter_id.as_ref() != Some(sender_id) {
+            return Err(ContractError::CallerIsNotStreamArbiter {
+                expected: stream.arbiter_id,
+                received: sender_id.clone(),
+            });
+        }
+        if !matches!(stream.status, StreamStatus::Disputed { .. }) {
+            return Err(ContractError::StreamNotDisputed {
+                stream_status: stream.status,
+            });
+        }
+        if receiver_amount > stream.balance {
+            return Err(ContractError::InvalidDisputeSplit {
+                balance: stream.balance,
+            });
+        }
+
+        // Validations passed
+        self.extract_stream(&stream_id)?;
+
+        stream.balance -= receiver_amount;
+        stream.tokens_awaiting_approval = 0;
+        let mut promises = self.process_extra_payment(&mut stream, receiver_amount)?;
+        promises.extend(self.process_action(
+            &mut stream,
+            ActionType::Stop {
+                reason: StreamFinishReason::ResolvedByArbiter,
+            },
+        )?);
+
+        self.save_stream(stream)?;
+
+        Ok(promises)
+    }
+
+    // Pledges receivables of the stream to the lender,
+    // the share of each payment goes to the lender until `amount` is repaid.
+    pub fn pledge_stream_op(
+        &mut self,
+        sender_id: &AccountId,
+        stream_id: CryptoHash,
+        lender_id: AccountId,
+        amount: Balance,
+        share_bps: u16,
+    ) -> Result<(), ContractError> {
+        let mut stream = self.view_stream(&stream_id)?;
+
+        if stream.receiver_id != *sender_id {
+            return Err(ContractError::CallerIsNotStreamReceiver {
+                expected: stream.receiver_id,
+                received: sender_id.clone(),
+            });
+        }
+        if !matches!(
+            stream.status,
+            StreamStatus::Initialized | StreamStatus::Active | StreamStatus::Paused
+        ) {
+            return Err(ContractError::CannotPledgeStream {
+                stream_status: stream.status,
+            });
+        }
+        if stream.pledge.is_some() {
+            return Err(ContractError::StreamPledged { stream_id });
+        }
+        if lender_id == stream.receiver_id || amount == 0 || share_bps == 0 || share_bps > 10_000 {
+            return Err(ContractError::InvalidPledge);
+        }
+
+        // Validations passed
+        self.extract_stream(&stream_id)?;
+
+        stream.pledge = Some(Pledge {
+            lender_id,
+            amount,
+            repaid: 0,
+            share_bps,
+        });
+
+        self.save_stream(stream)
+    }
+
+    // Releasing the pledge before it's repaid is the consent of the lender
+    // to stop the stream or change its receiver.
+    pub fn release_pledge_op(
+        &mut self,
+        sender_id: &AccountId,
+        stream_id: CryptoHash,
+    ) -> Result<(), ContractError> {
+        let mut stream = self.view_stream(&stream_id)?;
+
+        let lender_id = match &stream.pledge {
+            Some(pledge) => pledge.lender_id.clone(),
+            None => return Err(ContractError::StreamNotPledged { stream_id }),
+        };
+        if lender_id != *sender_id {
+            return Err(ContractError::CallerIsNotLender {
+                expected: lender_id,
+                received: sender_id.clone(),
+            });
+        }
+
+        // Validations passed
+        self.extract_stream(&stream_id)?;
+
+        stream.pledge = None;
+
+        self.save_stream(stream)
+    }
+
+    // Freezing stops accrual and any actions with the stream
+    // until the DAO unfreezes or claws it back.
+    pub fn freeze_stream_op(&mut self, stream_id: CryptoHash) -> Result<(), ContractError> {
+        let mut stream = self.view_stream(&stream_id)?;
+        check_clawback_allowed(&stream)?;
+        stream.check_not_frozen()?;
+
+        // Validations passed
+        self.extract_stream(&stream_id)?;
+
+        stream.frozen_at = Some(env::block_timestamp());
+        log!(
+            "stream {} frozen by DAO",
+            String::from(&Base58CryptoHash::from(stream_id))
+        );
+
+        self.save_stream(stream)
+    }
+
+    pub fn unfreeze_stream_op(&mut self, stream_id: CryptoHash) -> Result<(), ContractError> {
+        let mut stream = self.view_stream(&stream_id)?;
+        let frozen_at = match stream.frozen_at {
+            Some(frozen_at) => frozen_at,
+            None => return Err(ContractError::StreamNotFrozen { stream_id }),
+        };
+
+        // Validations passed
+        self.extract_stream(&stream_id)?;
+
+        // The time the stream was frozen is not accrued
+        stream.last_action += env::block_timestamp() - frozen_at;
+        stream.frozen_at = None;
+        log!(
+            "stream {} unfrozen by DAO",
+            String::from(&Base58CryptoHash::from(stream_id))
+        );
+
+        self.save_stream(stream)
+    }
+
+    // Stops the stream and refunds the balance to the owner.
+    // Tokens accrued and not withdrawn yet are paid to the receiver
+    // unless they are returned to the owner as well.
+    pub fn clawback_stream_op(
+        &mut self,
+        stream_id: CryptoHash,
+        is_unwithdrawn_returned: bool,
+    ) -> Result<Vec<Promise>, ContractError> {
+        let mut stream = self.view_stream(&stream_id)?;
+        check_clawback_allowed(&stream)?;
+
+        stream.update_cliff();
+
+        // Validations passed
+        self.extract_stream(&stream_id)?;
+
+        if is_unwithdrawn_returned {
+            // Nothing is accrued since the last action
+            stream.last_action = env::block_timestamp();
+            stream.tokens_awaiting_approval = 0;
+        }
+        let balance = stream.balance;
+        let tokens_total_withdrawn = stream.tokens_total_withdrawn;
+        let promises = self.process_action(
+            &mut stream,
+            ActionType::Stop {
+                reason: StreamFinishReason::ClawedBack,
+            },
+        )?;
+        let paid = stream.tokens_total_withdrawn - tokens_total_withdrawn;
+        log!(
+            "stream {} clawed back by DAO, {} tokens paid to the receiver, {} tokens refunded to the owner",
+            String::from(&Base58CryptoHash::from(stream_id)),
+            paid,
+            balance - paid
+        );
+
+        self.save_stream(stream)?;
+
+        Ok(promises)
+    }
+
+    // Checks the owner is allowed to stop the stream.
+    // Returns the notice period if the stop should be scheduled.
+    fn check_cancel_policy(&self, stream: &Stream) -> Result<Option<u32>, ContractError> {
+        if let Some(stop_at) = stream.stop_at {
+            return Err(ContractError::StopScheduled { stop_at });
+        }
+        let policy = match &stream.cancel_policy {
+            Some(policy) => policy,
+            None => return Ok(None),
+        };
+        if let Some(no_stop_before) = policy.no_stop_before {
+            if env::block_timestamp() < no_stop_before {
+                return Err(ContractError::StopNotAllowedUntil {
+                    timestamp: no_stop_before,
+                });
+            }
+        }
+        Ok(policy
+            .notice_period_sec
+            .filter(|period| *period > 0 && stream.status == StreamStatus::Active))
+    }
+
+    // Whether the sender is the owner or the receiver of the stream
+    // or is approved by any of them to make the action.
+    fn is_stream_actor(
+        &self,
+        sender_id: &AccountId,
+        action: OperatorAction,
+        stream: &Stream,
+    ) -> bool {
+        stream.owner_id == *sender_id
+            || stream.receiver_id == *sender_id
+            || self.is_operator(&stream.owner_id, sender_id, action, stream)
+            || self.is_operator(&stream.receiver_id, sender_id, action, stream)
+    }
+
+    pub fn approve_milestone_op(
+        &mut self,
+        sender_id: &AccountId,
+        stream_id: CryptoHash,
+    ) -> Result<(), ContractError> {
+        let mut stream = self.view_stream(&stream_id)?;
+        self.check_milestone_approver(sender_id, &stream)?;
+
+        // Validations passed
+        self.extract_stream(&stream_id)?;
+
+        stream.milestones_approved += 1;
+
+        self.save_stream(stream)
+    }
+
+    pub fn reject_milestone_op(
+        &mut self,
+        sender_id: &AccountId,
+        stream_id: CryptoHash,
+    ) -> Result<Vec<Promise>, ContractError> {
+        let mut stream = self.view_stream(&stream_id)?;
+        self.check_milestone_approver(sender_id, &stream)?;
+        stream.check_not_frozen()?;
+        if let StreamStatus::Disputed { .. } = stream.status {
+            return Err(ContractError::CannotStopStream {
+                stream_status: stream.status,
+            });
+        }
+
+        stream.update_cliff();
+
+        // Validations passed
+        self.extract_stream(&stream_id)?;
+
+        // Tokens approved are paid and the rest is refunded
+        let promises = self.process_action(
+            &mut stream,
+            ActionType::Stop {
+                reason: StreamFinishReason::RejectedByApprover,
+            },
+        )?;
+
+        self.save_stream(stream)?;
+
+        Ok(promises)
+    }
+
+    fn check_milestone_approver(
+        &self,
+        sender_id: &AccountId,
+        stream: &Stream,
+    ) -> Result<(), ContractError> {
+        if stream.approver_id.as_ref() != Some(sender_id) {
+            return Err(ContractError::CallerIsNotStreamApprover {
+                expected: stream.approver_id.clone(),
+                received: sender_id.clone(),
+            });
+        }
+        if stream.status.is_terminated() {
+            return Err(ContractError::StreamTerminated {
+                stream_id: stream.id,
+            });
+        }
+        // The last implicit milestone releases the rest of the stream
+        if stream.milestones_approved as usize > stream.milestones.len() {
+            return Err(ContractError::NoMilestonesLeft {
+                stream_id: stream.id,
+            });
+        }
+        Ok(())
+    }
+
+    // Processes streams one by one until gas is not enough for the next one.
+    // Errors of validations are reported per stream and don't affect other streams.
+    //
+    // Errors after the stream is touched would leave it half-processed,
+    // so the whole batch fails with such an error.
+    pub fn batch_op(
+        &mut self,
+        stream_ids: Vec<CryptoHash>,
+        gas_needed: impl Fn(&Self, &CryptoHash) -> Gas,
+        mut op: impl FnMut(&mut Self, CryptoHash) -> Result<(), ContractError>,
+    ) -> Result<BatchResult, ContractError> {
+        let mut results = vec![];
+        let mut stream_ids = stream_ids.into_iter();
+        for stream_id in stream_ids.by_ref() {
+            if check_gas(gas_needed(self, &stream_id)).is_err() {
+                return Ok(BatchResult {
+                    results,
+                    unprocessed: std::iter::once(stream_id)
+                        .chain(stream_ids)
+                        .map(|stream_id| stream_id.into())
+                        .collect(),
+                });
+            }
+            let stored = self.stored_stream(&stream_id);
+            match op(self, stream_id) {
+                Err(err) if self.stored_stream(&stream_id) != stored => return Err(err),
+                result => results.push(StreamResult { stream_id, result }),
+            }
+        }
+        Ok(BatchResult {
+            results,
+            unprocessed: vec![],
+        })
+    }
+
+    fn stored_stream(&self, stream_id: &CryptoHash) -> Option<Vec<u8>> {
+        self.streams
+            .get(stream_id)
+            .map(|vstream| vstream.try_to_vec().unwrap())
+    }
+
+    // Gas to pause or stop the stream within batch calls in the worst case
+    // of the transfers made. Keepers are not tipped on these actions.
+    pub(crate) fn gas_for_batch(&self, stream_id: &CryptoHash, is_stop: bool) -> Gas {
+        let stream = match self.view_stream(stream_id) {
+            Ok(stream) => stream,
+            // Nothing is transferred for streams failing validations
+            Err(_) => return GAS_FOR_BATCH_START,
+        };
+        let is_routed = self
+            .view_account(&stream.receiver_id, true)
+            .is_ok_and(|receiver| receiver.payout_route.is_some());
+        let mut gas_for_payment = if is_routed {
+            GAS_FOR_PAYOUT_FROM_FINANCE
+        } else {
+            GAS_FOR_FT_TRANSFER_FROM_FINANCE
+        };
+        if stream.pledge.is_some() {
+            // The lender is repaid with a separate transfer
+            gas_for_payment += GAS_FOR_FT_TRANSFER_FROM_FINANCE;
+        }
+
+        let mut gas_needed = GAS_FOR_BATCH_START + gas_for_payment;
+        if is_stop {
+            // The severance is paid as the payment, the rest is refunded
+            if stream
+                .cancel_policy
+                .as_ref()
+                .is_some_and(|policy| policy.severance > 0)
+            {
+                gas_needed += gas_for_payment;
+            }
+            gas_needed += GAS_FOR_FT_TRANSFER_FROM_FINANCE;
+        }
+        gas_needed
+    }
+
+    pub fn withdraw_op(
+        &mut self,
+        sender_id: &AccountId,
+        stream_id: CryptoHash,
+    ) -> Result<Vec<Promise>, ContractError> {
+        let mut stream = self.extract_stream(&stream_id)?;
+
+        let receiver_view = self.view_account(&stream.receiver_id, true)?;
+        let is_operator = self.is_operator(
+            &receiver_view.id,
+            sender_id,
+            OperatorAction::Withdraw,
+            &stream,
+        );
+
+        if receiver_view.id != *sender_id && !receiver_view.is_cron_allowed && !is_operator {
+            return Err(ContractError::CronCallsForbidden {
+                received: receiver_view.id,
+            });
+        }
+
+        if stream.status != StreamStatus::Active {
+            return Err(ContractError::CannotWithdraw {
+                stream_status: stream.status,
+            });
+        }
+        stream.check_not_frozen()?;
+
+        stream.update_cliff();
+
+        if stream.cliff.is_some() {
+            return Err(ContractError::CliffNotPassed {
+                timestamp: stream.cliff.unwrap(),
+            });
+        }
+
+        // Validations passed
+
+        // Operators act on behalf of the receiver and are not tipped
+        let keeper_id = if *sender_id != stream.receiver_id && !is_operator {
+            Some(sender_id.clone())
+        } else {
+            None
+        };
+        let mut promises = self.process_action(&mut stream, ActionType::Withdraw { keeper_id })?;
+        if stream.is_stop_due() && !stream.status.is_terminated() {
+            promises.extend(self.process_action(
+                &mut stream,
+                ActionType::Stop {
+                    reason: StreamFinishReason::StoppedByOwner,
+                },
+            )?);
+        }
+
+        self.save_stream(stream)?;
+
+        Ok(promises)
+    }
+
+    pub fn prune_stream_op(
+        &mut self,
+        sender_id: &AccountId,
+        stream_id: CryptoHash,
+        is_archived: bool,
+    ) -> Result<(), ContractError> {
+        let stream = self.extract_stream(&stream_id)?;
+
+        if stream.owner_id != *sender_id
+            && stream.receiver_id != *sender_id
+            && self.dao.dao_id != *sender_id
+        {
+            return Err(ContractError::CallerIsNotStreamActor {
+                owner: stream.owner_id,
+                receiver: stream.receiver_id,
+                caller: sender_id.clone(),
+            });
+        }
+        let reason = match stream.status {
+            StreamStatus::Finished { reason } => reason,
+            _ => {
+                return Err(ContractError::CannotPruneStream {
+                    stream_status: stream.status,
+                })
+            }
+        };
+        let prune_timestamp =
+            stream.last_action + self.dao.stream_prune_period_sec * TICKS_PER_SECOND;
+        if env::block_timestamp() < prune_timestamp {
+            return Err(ContractError::PrunePeriodNotPassed {
+                timestamp: prune_timestamp,
+            });
+        }
+
+        // Validations passed
+
+        // Terminated streams are already removed from the accounts
+        // while processing the last action, so only the stream itself
+        // is left to be removed.
+        let mut archived_storage_usage = 0;
+        if is_archived {
+            let storage_usage_initial = env::storage_usage();
+            let archived_stream = ArchivedStream {
+                id: stream.id,
+                creator_id: stream.creator_id.clone(),
+                owner_id: stream.owner_id,
+                receiver_id: stream.receiver_id,
+                token_account_id: stream.token_account_id,
+                timestamp_created: stream.timestamp_created,
+                timestamp_finished: stream.last_action,
+                tokens_total_withdrawn: stream.tokens_total_withdrawn,
+                reason,
+            };
+            check_integrity(
+                self.archived_streams
+                    .insert(&stream_id, &archived_stream)
+                    .is_none(),
+            )?;
+            archived_storage_usage = env::storage_usage() - storage_usage_initial;
+        }
+
+        if stream.storage_usage > 0 {
+            // Archived record is still paid by the creator.
+            let mut creator = self.extract_account(&stream.creator_id)?;
+            creator.release_storage(stream.storage_usage.saturating_sub(archived_storage_usage));
+            self.save_account(creator)?;
+        }
+
+        Ok(())
     }
 
     pub fn change_description_op(
@@ -416,7 +1557,14 @@ impl Contract {
             });
         }
 
-        if stream.owner_id != *sender_id {
+        if stream.owner_id != *sender_id
+            && !self.is_operator(
+                &stream.owner_id,
+                sender_id,
+                OperatorAction::ChangeDescription,
+                &stream,
+            )
+        {
             return Err(ContractError::CallerIsNotStreamOwner {
                 expected: stream.owner_id,
                 received: sender_id.clone(),
@@ -440,10 +1588,36 @@ impl Contract {
         stream_id: CryptoHash,
         new_receiver_id: AccountId,
         deposit_needed: Balance,
+        deposit: Balance,
     ) -> Result<Vec<Promise>, ContractError> {
+        if self.view_stream(&stream_id)?.pledge.is_some() {
+            return Err(ContractError::StreamPledged { stream_id });
+        }
+
         let mut promises = self.withdraw_op(prev_receiver_id, stream_id)?;
+        promises.extend(self.process_change_receiver(
+            prev_receiver_id,
+            stream_id,
+            new_receiver_id,
+            deposit_needed,
+            deposit,
+        )?);
 
-        let mut stream = self.extract_stream(&stream_id)?;
+        Ok(promises)
+    }
+
+    // Moves the receivership of the stream to the new receiver,
+    // nothing is paid to the previous one. Streams not active
+    // are moved within the inactive streams of the receivers.
+    pub(crate) fn process_change_receiver(
+        &mut self,
+        prev_receiver_id: &AccountId,
+        stream_id: CryptoHash,
+        new_receiver_id: AccountId,
+        deposit_needed: Balance,
+        deposit: Balance,
+    ) -> Result<Vec<Promise>, ContractError> {
+        let mut stream = self.view_stream(&stream_id)?;
 
         if stream.status.is_terminated() {
             return Err(ContractError::StreamTerminated {
@@ -457,6 +1631,10 @@ impl Contract {
             });
         }
 
+        // Validations passed
+        self.extract_stream(&stream_id)?;
+
+        let mut promises = vec![];
         let token = self.dao.get_token(&stream.token_account_id);
 
         let mut new_receiver = if let Ok(account) = self.extract_account(&new_receiver_id) {
@@ -486,7 +1664,12 @@ impl Contract {
                 self.stats_withdraw(&token, 0, token.commission_on_transfer);
             } else {
                 // Charge in NEAR
-                check_deposit(self.dao.commission_non_payment_ft + deposit_needed)?;
+                if deposit < self.dao.commission_non_payment_ft + deposit_needed {
+                    return Err(ContractError::InsufficientDeposit {
+                        expected: self.dao.commission_non_payment_ft + deposit_needed,
+                        received: deposit,
+                    });
+                }
                 self.stats_inc_account_deposit(self.dao.commission_non_payment_ft, false);
             }
             self.create_account_if_not_exist(&new_receiver_id)?;
@@ -495,30 +1678,132 @@ impl Contract {
 
         let mut prev_receiver = self.extract_account(prev_receiver_id)?;
 
-        check_integrity(prev_receiver.active_incoming_streams.remove(&stream_id))?;
-        check_integrity(new_receiver.active_incoming_streams.insert(&stream_id))?;
-
-        prev_receiver
-            .total_incoming
-            .entry(stream.token_account_id.clone())
-            .and_modify(|e| *e -= stream.tokens_per_sec);
-        new_receiver
-            .total_incoming
-            .entry(stream.token_account_id.clone())
-            .and_modify(|e| *e += stream.tokens_per_sec);
+        if stream.status == StreamStatus::Active {
+            check_integrity(prev_receiver.active_incoming_streams.remove(&stream_id))?;
+            check_integrity(new_receiver.active_incoming_streams.insert(&stream_id))?;
+
+            prev_receiver
+                .total_incoming
+                .entry(stream.token_account_id.clone())
+                .and_modify(|e| *e -= stream.tokens_per_sec);
+            new_receiver
+                .total_incoming
+                .entry(stream.token_account_id.clone())
+                .and_modify(|e| *e += stream.tokens_per_sec)
+                .or_insert(stream.tokens_per_sec);
+        } else {
+            check_integrity(prev_receiver.inactive_incoming_streams.remove(&stream_id))?;
+            check_integrity(new_receiver.inactive_incoming_streams.insert(&stream_id))?;
+        }
 
         self.save_account(prev_receiver)?;
         self.save_account(new_receiver)?;
 
-        let storage_deposit_promise = ext_storage_management::ext(token.account_id)
-            .with_attached_deposit(deposit_needed)
-            .with_static_gas(token.gas_for_storage_deposit)
-            .storage_deposit(Some(new_receiver_id.clone()), Some(true));
-        promises.push(storage_deposit_promise);
+        if deposit_needed > 0 {
+            let storage_deposit_promise = ext_storage_management::ext(token.account_id)
+                .with_attached_deposit(deposit_needed)
+                .with_static_gas(token.gas_for_storage_deposit)
+                .storage_deposit(Some(new_receiver_id.clone()), Some(true));
+            promises.push(storage_deposit_promise);
+        }
 
-        stream.receiver_id = new_receiver_id;
+        // The child stream is owned by the previous receiver
+        if let Some(child_stream_id) = stream.child_stream_id.take() {
+            // The child may be pruned already
+            if let Ok(mut child) = self.extract_stream(&child_stream_id.into()) {
+                child.parent_stream_id = None;
+                self.save_stream(child)?;
+            }
+        }
+        stream.receiver_id = new_receiver_id.clone();
         self.save_stream(stream)?;
 
+        // Approvals are given by the previous receiver
+        self.revoke_stream_nft_approvals(&stream_id, prev_receiver_id)?;
+        self.transfer_stream_nft_index(&stream_id, prev_receiver_id, &new_receiver_id);
+
         Ok(promises)
     }
 }
+
+// Checks the deposit to the stream, `StreamExpired` is returned
+// for the stream that must be stopped instead.
+fn check_stream_deposit(
+    stream: &mut Stream,
+    token_account_id: &AccountId,
+    amount: Balance,
+) -> Result<(), ContractError> {
+    if stream.status.is_terminated() {
+        return Err(ContractError::StreamTerminated {
+            stream_id: stream.id,
+        });
+    }
+
+    if stream.is_locked
+        && !matches!(
+            stream.status,
+            StreamStatus::Initialized | StreamStatus::Pending { .. }
+        )
+    {
+        return Err(ContractError::StreamLocked {
+            stream_id: stream.id,
+        });
+    }
+
+    if stream.funded_from_treasury {
+        return Err(ContractError::StreamFundedFromTreasury {
+            stream_id: stream.id,
+        });
+    }
+
+    stream.check_not_frozen()?;
+
+    stream.update_cliff();
+
+    if stream.cliff.is_some() {
+        return Err(ContractError::CliffNotPassed {
+            timestamp: stream.cliff.unwrap(),
+        });
+    }
+
+    if stream.available_to_withdraw() == stream.balance && stream.balance > 0 && stream.is_expirable
+    {
+        return Err(ContractError::StreamExpired {
+            stream_id: stream.id,
+        });
+    }
+
+    if stream.token_account_id != *token_account_id {
+        return Err(ContractError::InvalidToken {
+            expected: stream.token_account_id.clone(),
+            received: token_account_id.clone(),
+        });
+    }
+
+    if amount > MAX_AMOUNT || stream.balance + amount > MAX_AMOUNT {
+        return Err(ContractError::ExceededMaxBalance {
+            max_amount: MAX_AMOUNT,
+        });
+    }
+
+    Ok(())
+}
+
+fn check_clawback_allowed(stream: &Stream) -> Result<(), ContractError> {
+    if stream.status.is_terminated() {
+        return Err(ContractError::StreamTerminated {
+            stream_id: stream.id,
+        });
+    }
+    if stream.is_locked {
+        return Err(ContractError::StreamLocked {
+            stream_id: stream.id,
+        });
+    }
+    if !stream.is_clawback_allowed {
+        return Err(ContractError::ClawbackNotAllowed {
+            stream_id: stream.id,
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,282 @@
+use crate::*;
+
+// Subscription is an authorization for the merchant to charge
+// up to `amount` tokens once per period from the subscriber.
+//
+// Unlike streams, subscriptions hold no tokens.
+// Charges are taken from the subscriber's treasury of the token,
+// so they share the balance with streams funded from treasury.
+// Periods not charged by the merchant are not accumulated.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct Subscription {
+    #[serde(with = "b58_dec_format")]
+    pub id: CryptoHash,
+    pub subscriber_id: AccountId,
+    pub merchant_id: AccountId,
+    pub token_account_id: AccountId,
+
+    // Max amount to charge per period, including commission
+    #[serde(with = "u128_dec_format")]
+    pub amount: Balance,
+    pub period_sec: u32,
+
+    pub timestamp_created: Timestamp,
+    pub next_charge_at: Timestamp,
+
+    #[serde(with = "u128_dec_format")]
+    pub tokens_total_charged: Balance,
+
+    // Storage used by the subscription and charged to the subscriber.
+    pub storage_usage: StorageUsage,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub enum VSubscription {
+    Current(Subscription),
+}
+
+impl From<VSubscription> for Subscription {
+    fn from(v: VSubscription) -> Self {
+        match v {
+            VSubscription::Current(c) => c,
+        }
+    }
+}
+
+impl From<Subscription> for VSubscription {
+    fn from(c: Subscription) -> Self {
+        VSubscription::Current(c)
+    }
+}
+
+impl Subscription {
+    // The first charge is available immediately.
+    pub(crate) fn new(
+        salt: u64,
+        subscriber_id: AccountId,
+        merchant_id: AccountId,
+        token_account_id: AccountId,
+        amount: Balance,
+        period_sec: u32,
+    ) -> Subscription {
+        let mut buf = env::random_seed();
+        buf.append(&mut salt.to_le_bytes().to_vec());
+        buf.append(&mut subscriber_id.as_bytes().to_vec());
+        let id = env::sha256(&buf).as_slice().try_into().unwrap();
+        Self {
+            id,
+            subscriber_id,
+            merchant_id,
+            token_account_id,
+            amount,
+            period_sec,
+            timestamp_created: env::block_timestamp(),
+            next_charge_at: env::block_timestamp(),
+            tokens_total_charged: 0,
+            storage_usage: 0,
+        }
+    }
+
+    // Moves to the period after the current one.
+    fn process_charge(&mut self, amount: Balance) {
+        let period = self.period_sec as u64 * TICKS_PER_SECOND;
+        let periods_passed = (env::block_timestamp() - self.next_charge_at) / period + 1;
+        self.next_charge_at += periods_passed * period;
+        self.tokens_total_charged += amount;
+    }
+}
+
+impl Contract {
+    pub(crate) fn create_subscription_op(
+        &mut self,
+        subscriber_id: &AccountId,
+        merchant_id: AccountId,
+        token_account_id: AccountId,
+        amount: Balance,
+        period_sec: u32,
+    ) -> Result<SubscriptionId, ContractError> {
+        if amount == 0 {
+            return Err(ContractError::ZeroTokenTransfer);
+        }
+        if period_sec == 0 {
+            return Err(ContractError::InvalidSubscriptionPeriod);
+        }
+        // Subscriptions are not covered by finance contract
+        if !self
+            .view_account(subscriber_id, true)?
+            .is_storage_registered()
+        {
+            return Err(ContractError::AccountNotRegistered {
+                account_id: subscriber_id.clone(),
+            });
+        }
+        self.create_account_if_not_exist(&merchant_id)?;
+
+        // Validations passed
+
+        let storage_usage_initial = env::storage_usage();
+
+        let subscription = Subscription::new(
+            self.subscriptions.len(),
+            subscriber_id.clone(),
+            merchant_id,
+            token_account_id,
+            amount,
+            period_sec,
+        );
+        let subscription_id = subscription.id;
+
+        let mut merchant = self.extract_account(&subscription.merchant_id)?;
+        check_integrity(merchant.incoming_subscriptions.insert(&subscription_id))?;
+        self.save_account(merchant)?;
+
+        let mut subscriber = self.extract_account(subscriber_id)?;
+        check_integrity(subscriber.outgoing_subscriptions.insert(&subscription_id))?;
+        self.save_account(subscriber)?;
+        self.save_subscription(subscription)?;
+
+        let storage_usage = env::storage_usage() - storage_usage_initial;
+        let mut subscriber = self.extract_account(subscriber_id)?;
+        subscriber.charge_storage(storage_usage)?;
+        self.save_account(subscriber)?;
+
+        let mut subscription = self.extract_subscription(&subscription_id)?;
+        subscription.storage_usage = storage_usage;
+        self.save_subscription(subscription)?;
+
+        Ok(subscription_id)
+    }
+
+    pub(crate) fn charge_subscription_op(
+        &mut self,
+        sender_id: &AccountId,
+        subscription_id: CryptoHash,
+        amount: Option<Balance>,
+    ) -> Result<Option<Promise>, ContractError> {
+        let mut subscription = self.view_subscription(&subscription_id)?;
+
+        if subscription.merchant_id != *sender_id {
+            return Err(ContractError::CallerIsNotMerchant {
+                expected: subscription.merchant_id,
+                received: sender_id.clone(),
+            });
+        }
+        if env::block_timestamp() < subscription.next_charge_at {
+            return Err(ContractError::ChargePeriodNotPassed {
+                timestamp: subscription.next_charge_at,
+            });
+        }
+        let amount = amount.unwrap_or(subscription.amount);
+        if amount == 0 {
+            return Err(ContractError::ZeroTokenTransfer);
+        }
+        if amount > subscription.amount {
+            return Err(ContractError::InvalidTokenWithdrawAmount {
+                requested: amount,
+                left: subscription.amount,
+            });
+        }
+        let treasury = self
+            .view_account(&subscription.subscriber_id, true)?
+            .view_treasury(&subscription.token_account_id);
+        if amount > treasury.balance {
+            return Err(ContractError::InvalidTokenWithdrawAmount {
+                requested: amount,
+                left: treasury.balance,
+            });
+        }
+
+        // Validations passed
+        self.extract_subscription(&subscription_id)?;
+
+        let mut subscriber = self.extract_account(&subscription.subscriber_id)?;
+        let treasury = subscriber.treasury_mut(&subscription.token_account_id);
+        treasury.settle(env::block_timestamp());
+        treasury.balance -= amount;
+        self.save_account(subscriber)?;
+        self.update_token_treasury_balance(&subscription.token_account_id, 0, amount);
+
+        subscription.process_charge(amount);
+
+        let token = self.dao.get_token(&subscription.token_account_id);
+        let (payment, commission) = if token.is_payment {
+            token.apply_commission(amount)
+        } else {
+            (amount, 0)
+        };
+        // Treasury tokens are counted in TVL once charged
+        self.stats_inc_stream_deposit(&token.account_id, &amount, &0);
+        self.stats_withdraw(&token, payment, commission);
+
+        let merchant_id = subscription.merchant_id.clone();
+        self.save_subscription(subscription)?;
+
+        self.ft_transfer_from_finance(token.account_id, merchant_id, payment)
+    }
+
+    pub(crate) fn cancel_subscription_op(
+        &mut self,
+        sender_id: &AccountId,
+        subscription_id: CryptoHash,
+    ) -> Result<(), ContractError> {
+        let subscription = self.view_subscription(&subscription_id)?;
+
+        if subscription.subscriber_id != *sender_id {
+            return Err(ContractError::CallerIsNotSubscriber {
+                expected: subscription.subscriber_id,
+                received: sender_id.clone(),
+            });
+        }
+
+        // Validations passed
+        self.extract_subscription(&subscription_id)?;
+
+        let mut merchant = self.extract_account(&subscription.merchant_id)?;
+        check_integrity(merchant.incoming_subscriptions.remove(&subscription_id))?;
+        self.save_account(merchant)?;
+
+        let mut subscriber = self.extract_account(&subscription.subscriber_id)?;
+        check_integrity(subscriber.outgoing_subscriptions.remove(&subscription_id))?;
+        subscriber.release_storage(subscription.storage_usage);
+        self.save_account(subscriber)
+    }
+
+    pub(crate) fn view_subscription(
+        &self,
+        subscription_id: &SubscriptionId,
+    ) -> Result<Subscription, ContractError> {
+        match self.subscriptions.get(subscription_id) {
+            Some(vsubscription) => Ok(vsubscription.into()),
+            None => Err(ContractError::SubscriptionNotExist {
+                subscription_id: *subscription_id,
+            }),
+        }
+    }
+
+    pub(crate) fn extract_subscription(
+        &mut self,
+        subscription_id: &SubscriptionId,
+    ) -> Result<Subscription, ContractError> {
+        match self.subscriptions.remove(subscription_id) {
+            Some(vsubscription) => Ok(vsubscription.into()),
+            None => Err(ContractError::SubscriptionNotExist {
+                subscription_id: *subscription_id,
+            }),
+        }
+    }
+
+    pub(crate) fn save_subscription(
+        &mut self,
+        subscription: Subscription,
+    ) -> Result<(), ContractError> {
+        match self
+            .subscriptions
+            .insert(&subscription.id.clone(), &subscription.into())
+        {
+            None => Ok(()),
+            Some(_) => Err(ContractError::DataCorruption),
+        }
+    }
+}
@@ -60,6 +60,14 @@ pub trait ExtFinanceContract {
         receiver: AccountId,
         amount: U128,
     ) -> Promise;
+    fn streaming_ft_transfer_routed(
+        &mut self,
+        token_account_id: AccountId,
+        receiver: AccountId,
+        amount: U128,
+        msg: Option<String>,
+        fallback_id: AccountId,
+    ) -> Promise;
 }
 
 #[ext_contract]
@@ -80,7 +88,7 @@ impl Contract {
         }
 
         // TODO #16
-        let gas_needed = Gas::ONE_TERA * 50;
+        let gas_needed = GAS_FOR_FT_TRANSFER_FROM_FINANCE;
         check_gas(gas_needed)?;
         let promise = ext_finance_contract::ext(self.finance_id.clone())
             .with_attached_deposit(ONE_YOCTO)
@@ -89,6 +97,70 @@ impl Contract {
         Ok(Some(promise))
     }
 
+    // Transfers tokens withdrawn by the receiver along the payout route of the receiver.
+    pub(crate) fn payout_from_finance(
+        &self,
+        token_account_id: AccountId,
+        receiver: &Account,
+        amount: Balance,
+    ) -> Result<Option<Promise>, ContractError> {
+        let (destination_id, msg) = match &receiver.payout_route {
+            None => {
+                return self.ft_transfer_from_finance(token_account_id, receiver.id.clone(), amount)
+            }
+            Some(PayoutRoute::Account { account_id }) => (account_id.clone(), None),
+            Some(PayoutRoute::TransferCall { receiver_id, msg }) => {
+                (receiver_id.clone(), Some(msg.clone()))
+            }
+            Some(PayoutRoute::Restream {
+                receiver_id,
+                tokens_per_sec,
+            }) => {
+                let request = TransferCallRequest::Create {
+                    request: Box::new(CreateRequest {
+                        description: None,
+                        owner_id: receiver.id.clone(),
+                        receiver_id: receiver_id.clone(),
+                        tokens_per_sec: *tokens_per_sec,
+                        cliff_period_sec: None,
+                        is_auto_start_enabled: None,
+                        is_expirable: None,
+                        is_locked: None,
+                        balance: None,
+                        funded_from_treasury: None,
+                        approver_id: None,
+                        milestones: None,
+                        cancel_policy: None,
+                        is_clawback_allowed: None,
+                        arbiter_id: None,
+                    }),
+                };
+                (
+                    env::current_account_id(),
+                    Some(serde_json::to_string(&request).unwrap()),
+                )
+            }
+        };
+        if amount == 0 {
+            // NEP-141 forbids zero token transfers
+            return Ok(None);
+        }
+
+        let gas_needed = GAS_FOR_PAYOUT_FROM_FINANCE;
+        check_gas(gas_needed)?;
+        let promise = ext_finance_contract::ext(self.finance_id.clone())
+            .with_attached_deposit(ONE_YOCTO)
+            .with_static_gas(gas_needed)
+            .streaming_ft_transfer_routed(
+                token_account_id,
+                destination_id,
+                U128(amount),
+                msg,
+                receiver.id.clone(),
+            );
+        Ok(Some(promise))
+    }
+
     pub(crate) fn ft_transfer_from_self(
         &self,
         token_account_id: AccountId,
@@ -0,0 +1,251 @@
+use crate::*;
+
+// Owner's pool of tokens shared by streams funded from treasury.
+//
+// The pool is drained continuously by the outflow of its active streams,
+// the tokens drained are reserved for the streams and drawn by them
+// on their next action.
+//
+// Once the balance is not enough to cover the outflow, the treasury
+// becomes exhausted. All dependent streams accrue tokens until
+// the moment of exhaustion and are finished on their next action,
+// so it doesn't matter in which order the streams are touched.
+// Tokens deposited to exhausted treasury become available
+// when all its active streams are finished.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub struct Treasury {
+    #[serde(with = "u128_dec_format")]
+    pub balance: Balance,
+
+    // Sum of tokens_per_sec of active streams funded from treasury
+    #[serde(with = "u128_dec_format")]
+    pub outflow: Balance,
+    pub accrual_remainder: u64,
+    pub last_update: Timestamp,
+
+    pub exhausted_at: Option<Timestamp>,
+}
+
+impl Treasury {
+    // Drains the outflow from the balance up to the timestamp.
+    pub(crate) fn settle(&mut self, timestamp: Timestamp) {
+        if self.exhausted_at.is_none() && timestamp > self.last_update {
+            let period = timestamp - self.last_update;
+            let (mut reserved, mut remainder) =
+                accrue(period, self.outflow, self.accrual_remainder);
+            if reserved > self.balance {
+                // The latest moment the balance still covers the outflow
+                let period = accrual_period(
+                    self.balance.saturating_add(1),
+                    self.outflow,
+                    self.accrual_remainder,
+                ) - 1;
+                (reserved, remainder) = accrue(period, self.outflow, self.accrual_remainder);
+                self.exhausted_at = Some(self.last_update + period);
+            }
+            self.balance -= reserved;
+            self.accrual_remainder = remainder;
+        }
+        self.last_update = max(self.last_update, timestamp);
+    }
+
+    // The moment the balance is drained by the outflow, must be settled.
+    pub(crate) fn run_out_time(&self) -> Timestamp {
+        match self.exhausted_at {
+            Some(exhausted_at) => exhausted_at,
+            None if self.outflow == 0 => Timestamp::MAX,
+            None => self.last_update.saturating_add(accrual_period(
+                self.balance,
+                self.outflow,
+                self.accrual_remainder,
+            )),
+        }
+    }
+
+    pub(crate) fn is_solvent(&self) -> bool {
+        self.exhausted_at.is_none() && self.balance > 0
+    }
+}
+
+impl Account {
+    pub(crate) fn treasury_mut(&mut self, token_account_id: &AccountId) -> &mut Treasury {
+        self.treasury
+            .entry(token_account_id.clone())
+            .or_insert_with(|| Treasury {
+                last_update: env::block_timestamp(),
+                ..Default::default()
+            })
+    }
+
+    pub(crate) fn view_treasury(&self, token_account_id: &AccountId) -> Treasury {
+        self.view_treasury_at(token_account_id, env::block_timestamp())
+    }
+
+    // The treasury drained up to the moment.
+    pub(crate) fn view_treasury_at(
+        &self,
+        token_account_id: &AccountId,
+        timestamp: Timestamp,
+    ) -> Treasury {
+        let mut treasury = self
+            .treasury
+            .get(token_account_id)
+            .cloned()
+            .unwrap_or_default();
+        treasury.settle(timestamp);
+        treasury
+    }
+
+    pub(crate) fn deposit_to_treasury(&mut self, token_account_id: &AccountId, amount: Balance) {
+        let treasury = self.treasury_mut(token_account_id);
+        treasury.settle(env::block_timestamp());
+        treasury.balance += amount;
+    }
+
+    // Must be called with the treasury settled.
+    pub(crate) fn update_treasury_outflow(
+        &mut self,
+        stream: &Stream,
+        was_active: Option<bool>,
+        is_active: Option<bool>,
+    ) {
+        if was_active == Some(true) && is_active != Some(true) {
+            let treasury = self.treasury_mut(&stream.token_account_id);
+            treasury.outflow -= stream.tokens_per_sec;
+            if treasury.outflow == 0 {
+                // All dependent streams are finished or paused
+                treasury.exhausted_at = None;
+            }
+        } else if was_active != Some(true) && is_active == Some(true) {
+            self.treasury_mut(&stream.token_account_id).outflow += stream.tokens_per_sec;
+        }
+    }
+}
+
+impl Contract {
+    pub(crate) fn fund_treasury_op(
+        &mut self,
+        account_id: &AccountId,
+        token_account_id: AccountId,
+        amount: Balance,
+    ) -> Result<(), ContractError> {
+        // NEP-141 forbids zero-token transfers, so this should never happen.
+        assert_ne!(amount, 0);
+
+        self.create_account_if_not_exist(account_id)?;
+        let mut account = self.extract_account(account_id)?;
+        account.deposit_to_treasury(&token_account_id, amount);
+        self.save_account(account)?;
+        self.update_token_treasury_balance(&token_account_id, amount, 0);
+
+        self.ft_transfer_from_self(token_account_id, self.finance_id.clone(), amount)?;
+
+        Ok(())
+    }
+
+    pub(crate) fn withdraw_treasury_op(
+        &mut self,
+        account_id: &AccountId,
+        token_account_id: AccountId,
+        amount: Option<Balance>,
+    ) -> Result<Option<Promise>, ContractError> {
+        let mut account = self.extract_account(account_id)?;
+        let treasury = account.treasury_mut(&token_account_id);
+        treasury.settle(env::block_timestamp());
+        let amount = amount.unwrap_or(treasury.balance);
+        if amount > treasury.balance {
+            return Err(ContractError::InvalidTokenWithdrawAmount {
+                requested: amount,
+                left: treasury.balance,
+            });
+        }
+
+        // Withdrawing makes the active streams of the treasury
+        // finish earlier, as it's the same as stopping them.
+        treasury.balance -= amount;
+        self.save_account(account)?;
+        self.update_token_treasury_balance(&token_account_id, 0, amount);
+
+        self.ft_transfer_from_finance(token_account_id, account_id.clone(), amount)
+    }
+
+    // Moves tokens reserved for the stream into its balance.
+    // Returns whether the treasury is exhausted, the treasury must be settled.
+    pub(crate) fn draw_from_treasury(&mut self, stream: &mut Stream, owner: &Account) -> bool {
+        let exhausted_at = owner
+            .treasury
+            .get(&stream.token_account_id)
+            .and_then(|treasury| treasury.exhausted_at);
+        let (drawn, _) = stream.accrued_at(treasury_accrual_end(
+            stream,
+            exhausted_at,
+            env::block_timestamp(),
+        ));
+        stream.balance += drawn;
+        // Treasury tokens are counted in TVL once drawn by the stream
+        self.stats_inc_stream_deposit(&stream.token_account_id, &drawn, &0);
+        self.update_token_treasury_balance(&stream.token_account_id, 0, drawn);
+        exhausted_at.is_some()
+    }
+
+    // Treasury balances are summed by token to reconcile them
+    // with the balance of finance contract.
+    pub(crate) fn update_token_treasury_balance(
+        &mut self,
+        token_account_id: &AccountId,
+        deposited: Balance,
+        released: Balance,
+    ) {
+        if deposited == released {
+            return;
+        }
+        let mut token_streams = self
+            .token_streams
+            .get(token_account_id)
+            .unwrap_or_else(|| TokenStreams::new(token_account_id));
+        token_streams.treasury_balance += deposited;
+        token_streams.treasury_balance -= released;
+        self.token_streams.insert(token_account_id, &token_streams);
+    }
+
+    // Tokens the stream may withdraw now, according to its treasury.
+    pub(crate) fn treasury_available_to_withdraw(&self, stream: &Stream) -> Balance {
+        self.treasury_available_to_withdraw_at(stream, env::block_timestamp())
+    }
+
+    // Tokens the stream may withdraw at the moment, assuming no actions
+    // are made with the treasury until then.
+    pub(crate) fn treasury_available_to_withdraw_at(
+        &self,
+        stream: &Stream,
+        timestamp: Timestamp,
+    ) -> Balance {
+        if stream.status != StreamStatus::Active {
+            return 0;
+        }
+        let exhausted_at = self
+            .view_account(&stream.owner_id, false)
+            .ok()
+            .and_then(|owner| {
+                owner
+                    .view_treasury_at(&stream.token_account_id, timestamp)
+                    .exhausted_at
+            });
+        stream
+            .accrued_at(treasury_accrual_end(stream, exhausted_at, timestamp))
+            .0
+    }
+}
+
+// Streams accrue tokens until the treasury is exhausted.
+fn treasury_accrual_end(
+    stream: &Stream,
+    exhausted_at: Option<Timestamp>,
+    timestamp: Timestamp,
+) -> Timestamp {
+    exhausted_at.map_or(timestamp, |exhausted_at| {
+        max(exhausted_at, stream.last_action)
+    })
+}
@@ -0,0 +1,213 @@
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use crate::*;
+    use near_sdk::test_utils::test_env::{alice, bob, carol};
+    use near_sdk::{test_utils::VMContextBuilder, testing_env, ONE_NEAR};
+
+    fn finance_id() -> AccountId {
+        "finance.near".parse().unwrap()
+    }
+    fn dao_id() -> AccountId {
+        "dao.near".parse().unwrap()
+    }
+    fn utility_token_id() -> AccountId {
+        "utilitytoken.near".parse().unwrap()
+    }
+    fn token_id() -> AccountId {
+        "token.near".parse().unwrap()
+    }
+
+    fn new_contract() -> Contract {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(dao_id())
+            .attached_deposit(1)
+            .build());
+        let mut token = Token::new_unlisted(&token_id());
+        token.is_payment = true;
+        contract.dao_update_token(token).unwrap();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(token_id())
+            .build());
+        for receiver_id in [bob(), carol()] {
+            contract
+                .create_stream_op(
+                    alice(),
+                    token_id(),
+                    ONE_NEAR,
+                    CreateRequest {
+                        description: None,
+                        owner_id: alice(),
+                        receiver_id,
+                        tokens_per_sec: U128(1),
+                        cliff_period_sec: None,
+                        is_auto_start_enabled: None,
+                        is_expirable: None,
+                        is_locked: None,
+                        balance: None,
+                        funded_from_treasury: None,
+                        approver_id: None,
+                        milestones: None,
+                        cancel_policy: None,
+                        is_clawback_allowed: None,
+                        arbiter_id: None,
+                    },
+                )
+                .unwrap();
+        }
+        contract
+    }
+
+    #[test]
+    fn test_reconcile_tvl_paging() {
+        let mut contract = new_contract();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(dao_id())
+            .attached_deposit(1)
+            .build());
+
+        match contract.dao_reconcile_tvl(token_id(), Some(1)).unwrap() {
+            PromiseOrValue::Value(reconciliation) => {
+                assert_eq!(reconciliation.next_index, Some(1));
+                assert_eq!(reconciliation.streams_balance, ONE_NEAR);
+            }
+            PromiseOrValue::Promise(_) => panic!("all streams must not be scanned yet"),
+        }
+        assert!(matches!(
+            contract.dao_reconcile_tvl(token_id(), Some(1)).unwrap(),
+            PromiseOrValue::Promise(_)
+        ));
+        assert!(contract.tvl_reconciliations.get(&token_id()).is_none());
+    }
+
+    #[test]
+    fn test_reconcile_tvl_treasury() {
+        let mut contract = new_contract();
+        contract
+            .fund_treasury_op(&alice(), token_id(), 3 * ONE_NEAR)
+            .unwrap();
+        contract
+            .withdraw_treasury_op(&alice(), token_id(), Some(ONE_NEAR))
+            .unwrap();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(dao_id())
+            .attached_deposit(1)
+            .build());
+
+        match contract.dao_reconcile_tvl(token_id(), Some(1)).unwrap() {
+            PromiseOrValue::Value(reconciliation) => {
+                assert_eq!(reconciliation.next_index, Some(1));
+            }
+            PromiseOrValue::Promise(_) => panic!("all streams must not be scanned yet"),
+        }
+        // Actions between pages don't reorder the token index
+        let stream_id = contract
+            .view_account(&alice(), false)
+            .unwrap()
+            .last_created_stream
+            .unwrap();
+        contract.withdraw_op(&carol(), stream_id).unwrap();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(dao_id())
+            .attached_deposit(1)
+            .build());
+        assert!(matches!(
+            contract.dao_reconcile_tvl(token_id(), Some(1)).unwrap(),
+            PromiseOrValue::Promise(_)
+        ));
+
+        // Funded and not withdrawn from the treasury
+        assert_eq!(
+            contract
+                .token_streams
+                .get(&token_id())
+                .unwrap()
+                .treasury_balance,
+            2 * ONE_NEAR
+        );
+    }
+
+    #[test]
+    fn test_on_reconcile_tvl() {
+        let mut contract = new_contract();
+        let reconciliation = contract.on_reconcile_tvl(
+            Ok(U128(2 * ONE_NEAR)),
+            TvlReconciliation {
+                token_account_id: token_id(),
+                next_index: None,
+                streams_balance: 2 * ONE_NEAR,
+                treasury_balance: 0,
+                tvl: 2 * ONE_NEAR,
+                finance_balance: None,
+            },
+        );
+        assert_eq!(reconciliation.finance_balance, Some(U128(2 * ONE_NEAR)));
+
+        let reconciliation = contract.on_reconcile_tvl(
+            Err(PromiseError::Failed),
+            TvlReconciliation {
+                token_account_id: token_id(),
+                next_index: None,
+                streams_balance: 2 * ONE_NEAR,
+                treasury_balance: 0,
+                tvl: 2 * ONE_NEAR,
+                finance_balance: None,
+            },
+        );
+        assert!(reconciliation.finance_balance.is_none());
+    }
+
+    #[test]
+    fn test_reconcile_tvl_not_dao() {
+        let mut contract = new_contract();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(1)
+            .build());
+        assert!(contract.dao_reconcile_tvl(token_id(), None).is_err());
+    }
+
+    #[test]
+    fn test_upgrade_dao_v0() {
+        // Contract as stored by v2.2.0
+        #[derive(BorshSerialize)]
+        struct OldContract {
+            dao: DaoV0,
+            finance_id: AccountId,
+            accounts: UnorderedMap<AccountId, VAccount>,
+            streams: UnorderedMap<StreamId, VStream>,
+            stats: LazyOption<VStats>,
+        }
+
+        let contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        let dao = contract.dao;
+        env::state_write(&OldContract {
+            dao: DaoV0 {
+                dao_id: dao.dao_id,
+                tokens: dao.tokens,
+                commission_non_payment_ft: dao.commission_non_payment_ft,
+                utility_token_id: dao.utility_token_id,
+                utility_token_decimals: dao.utility_token_decimals,
+                eth_near_ratio: dao.eth_near_ratio,
+                oracles: dao.oracles,
+                approved_nfts: dao.approved_nfts,
+            },
+            finance_id: contract.finance_id,
+            accounts: contract.accounts,
+            streams: contract.streams,
+            stats: contract.stats,
+        });
+
+        let contract = Contract::upgrade();
+        assert_eq!(contract.dao.dao_id, dao_id());
+        assert_eq!(
+            contract.dao.commission_non_payment_ft,
+            DEFAULT_COMMISSION_NON_PAYMENT_FT
+        );
+        assert_eq!(
+            contract.dao.stream_prune_period_sec,
+            DEFAULT_STREAM_PRUNE_PERIOD_SEC
+        );
+    }
+}
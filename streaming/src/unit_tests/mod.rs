@@ -1,7 +1,13 @@
+mod dao;
+mod operator;
 mod primitives;
+mod storage;
 mod stream;
 mod stream_ops;
+mod subscription;
 mod token;
+mod treasury;
+mod views;
 
 // TODO more tests
 //
@@ -0,0 +1,204 @@
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use crate::*;
+    use near_sdk::test_utils::test_env::{alice, bob, carol};
+    use near_sdk::{test_utils::VMContextBuilder, testing_env};
+
+    const START: Timestamp = 1_000 * TICKS_PER_SECOND;
+
+    fn finance_id() -> AccountId {
+        "finance.near".parse().unwrap()
+    }
+    fn dao_id() -> AccountId {
+        "dao.near".parse().unwrap()
+    }
+    fn utility_token_id() -> AccountId {
+        "utilitytoken.near".parse().unwrap()
+    }
+    fn token_id() -> AccountId {
+        "token.near".parse().unwrap()
+    }
+
+    fn set_context(predecessor_id: AccountId, timestamp: Timestamp) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor_id)
+            .block_timestamp(timestamp)
+            .build());
+    }
+
+    fn operator(actions: Vec<OperatorAction>) -> Operator {
+        Operator {
+            actions,
+            stream_id: None,
+            token_account_id: None,
+            expires_at: None,
+        }
+    }
+
+    // Alice streams 100 tokens to Bob at 1 token per second.
+    fn new_contract() -> (Contract, StreamId) {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(dao_id())
+            .attached_deposit(1)
+            .build());
+        let mut token = Token::new_unlisted(&token_id());
+        token.is_payment = true;
+        contract.dao_update_token(token).unwrap();
+
+        set_context(token_id(), START);
+        let request = CreateRequest {
+            description: None,
+            owner_id: alice(),
+            receiver_id: bob(),
+            tokens_per_sec: U128(1),
+            cliff_period_sec: None,
+            is_auto_start_enabled: None,
+            is_expirable: None,
+            is_locked: None,
+            balance: Some(U128(100)),
+            funded_from_treasury: None,
+            approver_id: None,
+            milestones: None,
+            cancel_policy: None,
+            is_clawback_allowed: None,
+            arbiter_id: None,
+        };
+        let stream_id = contract
+            .create_streams_op(alice(), token_id(), 100, vec![request])
+            .unwrap()[0];
+        (contract, stream_id)
+    }
+
+    #[test]
+    fn test_operator_of_owner() {
+        let (mut contract, stream_id) = new_contract();
+        contract
+            .add_operator_op(
+                &alice(),
+                carol(),
+                operator(vec![OperatorAction::Pause, OperatorAction::Stop]),
+            )
+            .unwrap();
+
+        set_context(carol(), START + TICKS_PER_SECOND);
+        contract.pause_stream_op(&carol(), stream_id).unwrap();
+        assert_eq!(
+            contract.start_stream_op(&carol(), stream_id),
+            Err(ContractError::CallerIsNotStreamOwner {
+                expected: alice(),
+                received: carol(),
+            })
+        );
+
+        contract.stop_stream_op(&carol(), stream_id).unwrap();
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(
+            stream.status,
+            StreamStatus::Finished {
+                reason: StreamFinishReason::StoppedByOwner
+            }
+        );
+    }
+
+    #[test]
+    fn test_operator_scope_and_expiry() {
+        let (mut contract, stream_id) = new_contract();
+        let mut approval = operator(vec![OperatorAction::Withdraw]);
+        approval.token_account_id = Some(utility_token_id());
+        contract
+            .add_operator_op(&bob(), carol(), approval.clone())
+            .unwrap();
+
+        set_context(carol(), START + TICKS_PER_SECOND);
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert!(!contract.is_operator(&bob(), &carol(), OperatorAction::Withdraw, &stream));
+
+        // The approval is replaced
+        approval.token_account_id = None;
+        approval.stream_id = Some(stream_id.into());
+        approval.expires_at = Some(START + 2 * TICKS_PER_SECOND);
+        contract
+            .add_operator_op(&bob(), carol(), approval.clone())
+            .unwrap();
+        assert_eq!(
+            contract.view_account(&bob(), true).unwrap().operators[&carol()],
+            approval
+        );
+        contract.withdraw_op(&carol(), stream_id).unwrap();
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.tokens_total_withdrawn, 1);
+
+        set_context(carol(), START + 2 * TICKS_PER_SECOND);
+        assert_eq!(
+            contract.withdraw_op(&carol(), stream_id).err(),
+            Some(ContractError::CronCallsForbidden { received: bob() })
+        );
+    }
+
+    #[test]
+    fn test_operator_of_receiver_not_tipped() {
+        let (mut contract, stream_id) = new_contract();
+        contract
+            .add_operator_op(&bob(), carol(), operator(vec![OperatorAction::Withdraw]))
+            .unwrap();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .attached_deposit(1)
+            .build());
+        contract
+            .account_update_keeper_tip(Some(KeeperTip::Share { bps: 100 }))
+            .unwrap();
+
+        set_context(carol(), START + 100 * TICKS_PER_SECOND);
+        let promises = contract.withdraw_op(&carol(), stream_id).unwrap();
+        assert_eq!(promises.len(), 1);
+        let receiver = contract.view_account(&bob(), true).unwrap();
+        assert_eq!(receiver.total_received[&token_id()], 100);
+    }
+
+    #[test]
+    fn test_add_and_revoke_operator() {
+        let (mut contract, stream_id) = new_contract();
+        assert_eq!(
+            contract.add_operator_op(&alice(), carol(), operator(vec![])),
+            Err(ContractError::InvalidOperator)
+        );
+        let mut approval = operator(vec![OperatorAction::ChangeDescription]);
+        approval.expires_at = Some(START);
+        assert_eq!(
+            contract.add_operator_op(&alice(), carol(), approval),
+            Err(ContractError::InvalidOperator)
+        );
+        assert_eq!(
+            contract.add_operator_op(&alice(), alice(), operator(vec![OperatorAction::Stop])),
+            Err(ContractError::InvalidOperator)
+        );
+
+        contract
+            .add_operator_op(
+                &alice(),
+                carol(),
+                operator(vec![OperatorAction::ChangeDescription]),
+            )
+            .unwrap();
+        contract
+            .change_description_op(&carol(), stream_id, Some("payroll".to_string()))
+            .unwrap();
+
+        contract.revoke_operator_op(&alice(), carol()).unwrap();
+        assert_eq!(
+            contract.revoke_operator_op(&alice(), carol()),
+            Err(ContractError::OperatorNotExist {
+                operator_id: carol()
+            })
+        );
+        assert_eq!(
+            contract.change_description_op(&carol(), stream_id, None),
+            Err(ContractError::CallerIsNotStreamOwner {
+                expected: alice(),
+                received: carol(),
+            })
+        );
+    }
+}
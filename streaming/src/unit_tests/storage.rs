@@ -0,0 +1,186 @@
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use crate::*;
+    use near_sdk::test_utils::test_env::{alice, bob, carol};
+    use near_sdk::{test_utils::VMContextBuilder, testing_env, ONE_NEAR};
+
+    fn finance_id() -> AccountId {
+        "finance.near".parse().unwrap()
+    }
+    fn dao_id() -> AccountId {
+        "dao.near".parse().unwrap()
+    }
+    fn utility_token_id() -> AccountId {
+        "utilitytoken.near".parse().unwrap()
+    }
+    fn token_id() -> AccountId {
+        "token.near".parse().unwrap()
+    }
+
+    fn new_contract_with_creator(storage_balance: Balance) -> Contract {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(carol())
+            .attached_deposit(2 * DEFAULT_COMMISSION_NON_PAYMENT_FT)
+            .build());
+        contract.account_deposit_near().unwrap();
+        contract
+            .storage_deposit_op(&carol(), storage_balance, false)
+            .unwrap();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(token_id())
+            .build());
+        contract
+    }
+
+    fn create_stream(contract: &mut Contract) -> Result<(), ContractError> {
+        contract.create_stream_op(
+            carol(),
+            token_id(),
+            ONE_NEAR,
+            CreateRequest {
+                description: None,
+                owner_id: alice(),
+                receiver_id: bob(),
+                tokens_per_sec: U128(1),
+                cliff_period_sec: None,
+                is_auto_start_enabled: None,
+                is_expirable: None,
+                is_locked: None,
+                balance: None,
+                funded_from_treasury: None,
+                approver_id: None,
+                milestones: None,
+                cancel_policy: None,
+                is_clawback_allowed: None,
+                arbiter_id: None,
+            },
+        )
+    }
+
+    #[test]
+    fn test_storage_deposit_withdraw() {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        assert!(contract.storage_balance_of(alice()).is_none());
+
+        assert_eq!(
+            contract
+                .storage_deposit_op(&alice(), MIN_STORAGE_BALANCE - 1, false)
+                .err(),
+            Some(ContractError::InsufficientDeposit {
+                expected: MIN_STORAGE_BALANCE,
+                received: MIN_STORAGE_BALANCE - 1,
+            })
+        );
+
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        let balance = contract
+            .storage_deposit_op(&alice(), ONE_NEAR, true)
+            .unwrap();
+        assert_eq!(balance.total.0, MIN_STORAGE_BALANCE);
+        assert_eq!(balance.available.0, 0);
+
+        let balance = contract
+            .storage_deposit_op(&alice(), ONE_NEAR, false)
+            .unwrap();
+        assert_eq!(balance.total.0, ONE_NEAR + MIN_STORAGE_BALANCE);
+        assert_eq!(balance.available.0, ONE_NEAR);
+
+        let balance = contract.storage_withdraw_op(&alice(), None).unwrap();
+        assert_eq!(balance.total.0, MIN_STORAGE_BALANCE);
+        assert_eq!(balance.available.0, 0);
+
+        assert_eq!(contract.storage_unregister_op(&bob(), false), Ok(false));
+        assert_eq!(contract.storage_unregister_op(&alice(), false), Ok(true));
+        assert!(contract.storage_balance_of(alice()).is_none());
+
+        contract
+            .storage_deposit_op(&alice(), ONE_NEAR, false)
+            .unwrap();
+        assert_eq!(
+            contract.storage_withdraw_op(&alice(), Some(ONE_NEAR)).err(),
+            Some(ContractError::InsufficientStorageBalance {
+                requested: ONE_NEAR,
+                left: ONE_NEAR - MIN_STORAGE_BALANCE,
+            })
+        );
+    }
+
+    #[test]
+    fn test_storage_deposit_min() {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        let balance = contract
+            .storage_deposit_op(&alice(), MIN_STORAGE_BALANCE, false)
+            .unwrap();
+        assert_eq!(balance.total.0, MIN_STORAGE_BALANCE);
+        assert_eq!(balance.available.0, 0);
+
+        // Registered already, nothing to withdraw
+        let balance = contract
+            .storage_deposit_op(&alice(), MIN_STORAGE_BALANCE, true)
+            .unwrap();
+        assert_eq!(balance.total.0, MIN_STORAGE_BALANCE);
+        assert_eq!(
+            contract.storage_withdraw_op(&alice(), Some(1)).err(),
+            Some(ContractError::InsufficientStorageBalance {
+                requested: 1,
+                left: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_stream_storage_insufficient() {
+        // Registered creator must cover the storage of the stream
+        let mut contract = new_contract_with_creator(MIN_STORAGE_BALANCE);
+        assert!(matches!(
+            create_stream(&mut contract),
+            Err(ContractError::InsufficientStorageBalance { .. })
+        ));
+    }
+
+    #[test]
+    fn test_stream_storage_charged_to_creator() {
+        let mut contract = new_contract_with_creator(ONE_NEAR);
+        let storage_usage_initial = env::storage_usage();
+        create_stream(&mut contract).unwrap();
+
+        // Accounts of the owner and the receiver are created by the creator
+        // and recorded as the storage of the stream
+        let creator = contract.view_account(&carol(), true).unwrap();
+        let stream = contract
+            .view_stream(&creator.last_created_stream.unwrap())
+            .unwrap();
+        assert!(stream.storage_usage > 0);
+        assert_eq!(creator.storage_usage, stream.storage_usage);
+        assert_eq!(
+            creator.storage_usage,
+            env::storage_usage() - storage_usage_initial
+        );
+        assert_eq!(
+            contract.storage_balance_of(carol()).unwrap().available.0,
+            ONE_NEAR
+                - MIN_STORAGE_BALANCE
+                - creator.storage_usage as Balance * env::storage_byte_cost()
+        );
+
+        // Existing accounts are not charged again
+        create_stream(&mut contract).unwrap();
+        let storage_usage = creator.storage_usage;
+        let creator = contract.view_account(&carol(), true).unwrap();
+        let next_stream = contract
+            .view_stream(&creator.last_created_stream.unwrap())
+            .unwrap();
+        assert!(next_stream.storage_usage < stream.storage_usage);
+        assert_eq!(
+            creator.storage_usage,
+            storage_usage + next_stream.storage_usage
+        );
+        assert_eq!(
+            contract.storage_unregister_op(&carol(), true),
+            Err(ContractError::CannotUnregisterAccount {
+                account_id: carol()
+            })
+        );
+    }
+}
@@ -21,10 +21,79 @@ mod tests {
             cliff: None,
             is_expirable: true,
             is_locked: false,
+            funded_from_treasury: false,
+            approver_id: None,
+            milestones: vec![],
+            milestones_approved: 0,
+            tokens_awaiting_approval: 0,
+            child_stream_id: None,
+            parent_stream_id: None,
+            settled_debt: 0,
+            insolvent_since: None,
+            cancel_policy: None,
+            stop_at: None,
+            is_clawback_allowed: false,
+            frozen_at: None,
+            arbiter_id: None,
+            pledge: None,
+            accrual_remainder: 0,
+            storage_usage: 0,
             available_to_withdraw_by_formula: 0,
+            debt: 0,
         }
     }
 
+    #[test]
+    fn test_stream_v0_migration() {
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(1633333333000000000)
+            .build());
+        let stream = new_stream();
+        let legacy = StreamV0 {
+            id: stream.id,
+            description: stream.description.clone(),
+            creator_id: stream.creator_id.clone(),
+            owner_id: stream.owner_id.clone(),
+            receiver_id: stream.receiver_id.clone(),
+            token_account_id: stream.token_account_id.clone(),
+            timestamp_created: stream.timestamp_created,
+            last_action: stream.last_action,
+            balance: stream.balance,
+            tokens_per_sec: stream.tokens_per_sec,
+            status: StreamStatus::Active,
+            tokens_total_withdrawn: stream.tokens_total_withdrawn,
+            cliff: stream.cliff,
+            is_expirable: stream.is_expirable,
+            is_locked: stream.is_locked,
+        };
+        // Stored before the upgrade with the only variant at index 0
+        let mut bytes = vec![0u8];
+        bytes.extend(legacy.try_to_vec().unwrap());
+        let migrated: Stream = VStream::try_from_slice(&bytes).unwrap().into();
+        assert_eq!(migrated.try_to_vec().unwrap(), stream.try_to_vec().unwrap());
+
+        // Saved streams are read back as current
+        let bytes = VStream::from(migrated).try_to_vec().unwrap();
+        assert_eq!(bytes[0], 1);
+        let current: Stream = VStream::try_from_slice(&bytes).unwrap().into();
+        assert_eq!(current.try_to_vec().unwrap(), stream.try_to_vec().unwrap());
+    }
+
+    #[test]
+    fn test_stream_status_layout() {
+        // Variants stored before the upgrade keep their indexes
+        let index = |status: StreamStatus| status.try_to_vec().unwrap()[0];
+        assert_eq!(index(StreamStatus::Initialized), 0);
+        assert_eq!(index(StreamStatus::Active), 1);
+        assert_eq!(index(StreamStatus::Paused), 2);
+        assert_eq!(
+            index(StreamStatus::Finished {
+                reason: StreamFinishReason::StoppedByOwner
+            }),
+            3
+        );
+    }
+
     #[test]
     fn test_available_to_withdraw_all() {
         testing_env!(VMContextBuilder::new()
@@ -39,6 +108,94 @@ mod tests {
         assert_eq!(stream.available_to_withdraw(), stream.balance);
     }
 
+    #[test]
+    fn test_run_out_time() {
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(1633333333000000000)
+            .build());
+        let mut stream = new_stream();
+        // 1000 seconds
+        let run_out_time = stream.run_out_time();
+        assert_eq!(run_out_time, 1633333333000000000 + 1000 * TICKS_PER_SECOND);
+        assert!(stream.available_to_withdraw_at(run_out_time - 1) < stream.balance);
+        assert_eq!(
+            stream.available_to_withdraw_at(run_out_time),
+            stream.balance
+        );
+
+        stream.balance += 1;
+        assert_eq!(stream.run_out_time(), run_out_time + 1);
+    }
+
+    #[test]
+    fn test_process_withdraw_sub_second() {
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(1633333333000000000)
+            .build());
+        let mut stream = new_stream();
+        stream.tokens_per_sec = 3;
+        stream.balance = 1000;
+        // Withdrawing each 0.7 second for 7 seconds
+        for i in 1..=10u64 {
+            testing_env!(VMContextBuilder::new()
+                .block_timestamp(1633333333000000000 + i * 700_000_000)
+                .build());
+            stream.process_withdraw(&Token::new_unlisted(&"token1.near".parse().unwrap()));
+        }
+        assert_eq!(stream.tokens_total_withdrawn, 21);
+        assert_eq!(stream.accrual_remainder, 0);
+
+        // 1/3 second
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(1633333340000000000 + 333_333_334)
+            .build());
+        assert_eq!(stream.available_to_withdraw(), 1);
+        stream.process_withdraw(&Token::new_unlisted(&"token1.near".parse().unwrap()));
+        assert_eq!(stream.accrual_remainder, 2);
+        assert_eq!(
+            stream.run_out_time(),
+            1633333340000000000 + 333_333_334 + (978 * TICKS_PER_SECOND - 2).div_ceil(3)
+        );
+    }
+
+    #[test]
+    fn test_process_withdraw_milestones() {
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(1633333333000000000)
+            .build());
+        let mut stream = new_stream();
+        stream.tokens_per_sec = 1;
+        stream.balance = 100;
+        stream.approver_id = Some(alice());
+        stream.milestones = vec![10, 30];
+        let token = Token::new_unlisted(&"token1.near".parse().unwrap());
+
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(1633333353000000000)
+            .build());
+        assert_eq!(stream.available_to_withdraw(), 0);
+        assert_eq!(stream.process_withdraw(&token), (0, 0));
+        assert_eq!(stream.tokens_awaiting_approval, 20);
+
+        stream.milestones_approved = 1;
+        assert_eq!(stream.process_withdraw(&token), (10, 0));
+        assert_eq!(stream.tokens_awaiting_approval, 10);
+
+        // Tokens keep accruing beyond the approved milestone
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(1633333363000000000)
+            .build());
+        stream.milestones_approved = 2;
+        assert_eq!(stream.available_to_withdraw(), 20);
+
+        // The last implicit milestone releases the rest
+        stream.milestones_approved = 3;
+        assert_eq!(stream.process_withdraw(&token), (20, 0));
+        assert_eq!(stream.tokens_awaiting_approval, 0);
+        assert_eq!(stream.balance, 70);
+        assert_eq!(stream.status, StreamStatus::Active);
+    }
+
     #[test]
     fn test_process_withdraw_instant() {
         testing_env!(VMContextBuilder::new()
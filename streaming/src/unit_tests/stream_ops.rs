@@ -2,7 +2,7 @@
 mod tests {
     use crate::*;
     use near_sdk::test_utils::test_env::{alice, bob, carol};
-    use near_sdk::{env, test_utils::VMContextBuilder, testing_env};
+    use near_sdk::{env, test_utils::VMContextBuilder, testing_env, ONE_NEAR};
 
     fn finance_id() -> AccountId {
         "finance.near".parse().unwrap()
@@ -31,10 +31,72 @@ mod tests {
             cliff: None,
             is_expirable: true,
             is_locked: false,
+            funded_from_treasury: false,
+            approver_id: None,
+            milestones: vec![],
+            milestones_approved: 0,
+            tokens_awaiting_approval: 0,
+            child_stream_id: None,
+            parent_stream_id: None,
+            settled_debt: 0,
+            insolvent_since: None,
+            cancel_policy: None,
+            stop_at: None,
+            is_clawback_allowed: false,
+            frozen_at: None,
+            arbiter_id: None,
+            pledge: None,
+            accrual_remainder: 0,
+            storage_usage: 0,
             available_to_withdraw_by_formula: 0,
+            debt: 0,
         }
     }
 
+    fn create_stream(contract: &mut Contract) -> StreamId {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(carol())
+            .attached_deposit(DEFAULT_COMMISSION_NON_PAYMENT_FT)
+            .build());
+        contract.account_deposit_near().unwrap();
+        contract
+            .storage_deposit_op(&carol(), ONE_NEAR, false)
+            .unwrap();
+        let stream = new_stream();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(stream.token_account_id.clone())
+            .build());
+        contract
+            .create_stream_op(
+                carol(),
+                stream.token_account_id,
+                stream.balance,
+                CreateRequest {
+                    description: stream.description,
+                    owner_id: stream.owner_id,
+                    receiver_id: stream.receiver_id,
+                    tokens_per_sec: U128(stream.tokens_per_sec),
+                    cliff_period_sec: None,
+                    is_auto_start_enabled: None,
+                    is_expirable: None,
+                    is_locked: None,
+                    balance: None,
+                    funded_from_treasury: None,
+                    approver_id: None,
+                    milestones: None,
+                    cancel_policy: None,
+                    is_clawback_allowed: None,
+                    arbiter_id: None,
+                },
+            )
+            .unwrap();
+        contract
+            .view_account(&carol(), true)
+            .unwrap()
+            .last_created_stream
+            .unwrap()
+    }
+
     #[test]
     fn test_save_extract_stream() {
         let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
@@ -53,17 +115,26 @@ mod tests {
         let stream = new_stream();
         assert_eq!(
             contract.create_stream_op(
-                stream.description,
                 carol(),
-                stream.owner_id,
-                stream.receiver_id,
                 stream.token_account_id,
                 stream.balance,
-                stream.tokens_per_sec,
-                None,
-                None,
-                None,
-                None,
+                CreateRequest {
+                    description: stream.description,
+                    owner_id: stream.owner_id,
+                    receiver_id: stream.receiver_id,
+                    tokens_per_sec: U128(stream.tokens_per_sec),
+                    cliff_period_sec: None,
+                    is_auto_start_enabled: None,
+                    is_expirable: None,
+                    is_locked: None,
+                    balance: None,
+                    funded_from_treasury: None,
+                    approver_id: None,
+                    milestones: None,
+                    cancel_policy: None,
+                    is_clawback_allowed: None,
+                    arbiter_id: None,
+                },
             ),
             Err(ContractError::InsufficientNearBalance {
                 requested: 100000000000000000000000u128,
@@ -84,17 +155,26 @@ mod tests {
             .build());
         assert!(contract
             .create_stream_op(
-                stream.description,
                 carol(),
-                stream.owner_id,
-                stream.receiver_id,
                 stream.token_account_id,
                 stream.balance,
-                stream.tokens_per_sec,
-                None,
-                None,
-                None,
-                None,
+                CreateRequest {
+                    description: stream.description,
+                    owner_id: stream.owner_id,
+                    receiver_id: stream.receiver_id,
+                    tokens_per_sec: U128(stream.tokens_per_sec),
+                    cliff_period_sec: None,
+                    is_auto_start_enabled: None,
+                    is_expirable: None,
+                    is_locked: None,
+                    balance: None,
+                    funded_from_treasury: None,
+                    approver_id: None,
+                    milestones: None,
+                    cancel_policy: None,
+                    is_clawback_allowed: None,
+                    arbiter_id: None,
+                },
             )
             .is_ok());
     }
@@ -117,18 +197,1960 @@ mod tests {
             .build());
         assert!(contract
             .create_stream_op(
-                stream.description,
                 carol(),
-                stream.owner_id,
-                stream.receiver_id,
                 stream.token_account_id,
                 stream.balance,
-                stream.tokens_per_sec,
-                None,
-                None,
-                None,
-                None,
+                CreateRequest {
+                    description: stream.description,
+                    owner_id: stream.owner_id,
+                    receiver_id: stream.receiver_id,
+                    tokens_per_sec: U128(stream.tokens_per_sec),
+                    cliff_period_sec: None,
+                    is_auto_start_enabled: None,
+                    is_expirable: None,
+                    is_locked: None,
+                    balance: None,
+                    funded_from_treasury: None,
+                    approver_id: None,
+                    milestones: None,
+                    cancel_policy: None,
+                    is_clawback_allowed: None,
+                    arbiter_id: None,
+                },
             )
             .is_ok());
     }
+
+    fn create_stream_by_message(contract: &mut Contract, request: CreateRequest) -> Stream {
+        let msg = serde_json::to_string(&TransferCallRequest::Create {
+            request: Box::new(request),
+        })
+        .unwrap();
+        contract.ft_on_transfer(alice(), U128(10), msg);
+        let stream_id = contract
+            .view_account(&alice(), false)
+            .unwrap()
+            .last_created_stream
+            .unwrap();
+        contract.view_stream(&stream_id).unwrap()
+    }
+
+    #[test]
+    fn test_create_stream_message_funded_from_treasury() {
+        let mut contract = new_contract_with_payment_token();
+        let mut request = create_request(bob(), None);
+        request.funded_from_treasury = Some(true);
+        let stream = create_stream_by_message(&mut contract, request);
+        assert!(stream.funded_from_treasury);
+        assert_eq!(stream.balance, 0);
+    }
+
+    #[test]
+    fn test_create_stream_message_milestones() {
+        let mut contract = new_contract_with_payment_token();
+        let mut request = create_request(bob(), None);
+        request.approver_id = Some(carol());
+        request.milestones = Some(vec![U128(3)]);
+        let stream = create_stream_by_message(&mut contract, request);
+        assert_eq!(stream.approver_id, Some(carol()));
+        assert_eq!(stream.milestones, vec![3]);
+    }
+
+    #[test]
+    fn test_create_stream_message_cancel_policy() {
+        let mut contract = new_contract_with_payment_token();
+        let policy = CancelPolicy {
+            no_stop_before: None,
+            notice_period_sec: Some(5),
+            severance: 1,
+        };
+        let mut request = create_request(bob(), None);
+        request.cancel_policy = Some(policy.clone());
+        let stream = create_stream_by_message(&mut contract, request);
+        assert_eq!(stream.cancel_policy, Some(policy));
+    }
+
+    #[test]
+    fn test_create_stream_message_clawback_allowed() {
+        let mut contract = new_contract_with_payment_token();
+        let mut request = create_request(bob(), None);
+        request.is_clawback_allowed = Some(true);
+        let stream = create_stream_by_message(&mut contract, request);
+        assert!(stream.is_clawback_allowed);
+    }
+
+    #[test]
+    fn test_create_stream_message_arbiter() {
+        let mut contract = new_contract_with_payment_token();
+        let mut request = create_request(bob(), None);
+        request.arbiter_id = Some(carol());
+        let stream = create_stream_by_message(&mut contract, request);
+        assert_eq!(stream.arbiter_id, Some(carol()));
+    }
+
+    #[test]
+    fn test_prune_active_stream() {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        let stream_id = create_stream(&mut contract);
+        assert!(matches!(
+            contract.prune_stream_op(&alice(), stream_id, true),
+            Err(ContractError::CannotPruneStream { .. })
+        ));
+    }
+
+    #[test]
+    fn test_prune_recently_stopped_stream() {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        let stream_id = create_stream(&mut contract);
+        contract.stop_stream_op(&alice(), stream_id).unwrap();
+        assert_eq!(
+            contract.prune_stream_op(&bob(), stream_id, true),
+            Err(ContractError::PrunePeriodNotPassed {
+                timestamp: DEFAULT_STREAM_PRUNE_PERIOD_SEC * TICKS_PER_SECOND,
+            })
+        );
+    }
+
+    #[test]
+    fn test_prune_period_updated_by_dao() {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        let stream_id = create_stream(&mut contract);
+        contract.stop_stream_op(&alice(), stream_id).unwrap();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(1)
+            .build());
+        assert_eq!(
+            contract.dao_update_stream_prune_period(60),
+            Err(ContractError::CallerIsNotDao {
+                expected: dao_id(),
+                received: alice(),
+            })
+        );
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(dao_id())
+            .attached_deposit(1)
+            .build());
+        contract.dao_update_stream_prune_period(60).unwrap();
+
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(60 * TICKS_PER_SECOND)
+            .build());
+        contract.prune_stream_op(&bob(), stream_id, true).unwrap();
+        assert!(contract.view_stream(&stream_id).is_err());
+    }
+
+    #[test]
+    fn test_prune_stream() {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        let stream_id = create_stream(&mut contract);
+        let storage_usage = contract.view_account(&carol(), true).unwrap().storage_usage;
+        assert!(storage_usage > 0);
+
+        contract.stop_stream_op(&alice(), stream_id).unwrap();
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(DEFAULT_STREAM_PRUNE_PERIOD_SEC * TICKS_PER_SECOND)
+            .build());
+        contract.prune_stream_op(&bob(), stream_id, true).unwrap();
+
+        assert!(contract.view_stream(&stream_id).is_err());
+        let archived_stream = contract.archived_streams.get(&stream_id).unwrap();
+        assert_eq!(archived_stream.creator_id, carol());
+        assert_eq!(archived_stream.reason, StreamFinishReason::StoppedByOwner);
+        // Archived record is smaller than the stream
+        let creator = contract.view_account(&carol(), true).unwrap();
+        assert!(creator.storage_usage < storage_usage);
+    }
+
+    #[test]
+    fn test_prune_stream_without_archive() {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        // Accounts created along with the stream are released with it
+        let stream_id = create_stream(&mut contract);
+        assert!(contract.view_account(&alice(), true).is_ok());
+        contract.stop_stream_op(&bob(), stream_id).unwrap();
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(DEFAULT_STREAM_PRUNE_PERIOD_SEC * TICKS_PER_SECOND)
+            .build());
+        contract
+            .prune_stream_op(&dao_id(), stream_id, false)
+            .unwrap();
+
+        assert!(contract.view_stream(&stream_id).is_err());
+        assert!(contract.archived_streams.get(&stream_id).is_none());
+        let creator = contract.view_account(&carol(), true).unwrap();
+        assert_eq!(creator.storage_usage, 0);
+        assert_eq!(contract.storage_unregister_op(&carol(), true), Ok(true));
+    }
+
+    fn create_request(receiver_id: AccountId, balance: Option<Balance>) -> CreateRequest {
+        CreateRequest {
+            description: None,
+            owner_id: alice(),
+            receiver_id,
+            tokens_per_sec: U128(1),
+            cliff_period_sec: None,
+            is_auto_start_enabled: None,
+            is_expirable: None,
+            is_locked: None,
+            balance: balance.map(U128),
+            funded_from_treasury: None,
+            approver_id: None,
+            milestones: None,
+            cancel_policy: None,
+            is_clawback_allowed: None,
+            arbiter_id: None,
+        }
+    }
+
+    fn new_contract_with_payment_token() -> Contract {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(dao_id())
+            .attached_deposit(1)
+            .build());
+        let mut token = Token::new_unlisted(&new_stream().token_account_id);
+        token.is_payment = true;
+        contract.dao_update_token(token).unwrap();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(new_stream().token_account_id)
+            .build());
+        contract
+    }
+
+    #[test]
+    fn test_milestone_approve_and_reject() {
+        let mut contract = new_contract_with_payment_token();
+        let mut request = create_request(bob(), Some(10));
+        request.approver_id = Some(carol());
+        request.milestones = Some(vec![U128(3)]);
+        let stream_id = contract
+            .create_streams_op(alice(), new_stream().token_account_id, 10, vec![request])
+            .unwrap()[0];
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .block_timestamp(5 * TICKS_PER_SECOND)
+            .build());
+        contract.withdraw_op(&bob(), stream_id).unwrap();
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.tokens_total_withdrawn, 0);
+        assert_eq!(stream.tokens_awaiting_approval, 5);
+
+        assert_eq!(
+            contract.approve_milestone_op(&bob(), stream_id),
+            Err(ContractError::CallerIsNotStreamApprover {
+                expected: Some(carol()),
+                received: bob(),
+            })
+        );
+        contract.approve_milestone_op(&carol(), stream_id).unwrap();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .block_timestamp(6 * TICKS_PER_SECOND)
+            .build());
+        contract.withdraw_op(&bob(), stream_id).unwrap();
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.tokens_total_withdrawn, 3);
+        assert_eq!(stream.tokens_awaiting_approval, 3);
+
+        // Unvested remainder is refunded to the owner
+        let promises = contract.reject_milestone_op(&carol(), stream_id).unwrap();
+        assert_eq!(promises.len(), 1);
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.tokens_total_withdrawn, 3);
+        assert_eq!(stream.balance, 0);
+        assert_eq!(stream.tokens_awaiting_approval, 0);
+        assert_eq!(
+            stream.status,
+            StreamStatus::Finished {
+                reason: StreamFinishReason::RejectedByApprover
+            }
+        );
+    }
+
+    #[test]
+    fn test_create_stream_invalid_milestones() {
+        let mut contract = new_contract_with_payment_token();
+        for milestones in [vec![0, 1], vec![2, 2], vec![3, 1]] {
+            let mut request = create_request(bob(), Some(10));
+            request.approver_id = Some(carol());
+            request.milestones = Some(milestones.into_iter().map(U128).collect());
+            assert_eq!(
+                contract.create_streams_op(
+                    alice(),
+                    new_stream().token_account_id,
+                    10,
+                    vec![request]
+                ),
+                Err(ContractError::InvalidMilestones {
+                    max_milestones: MAX_MILESTONES
+                })
+            );
+        }
+        let mut request = create_request(bob(), Some(10));
+        request.milestones = Some(vec![U128(1)]);
+        assert!(contract
+            .create_streams_op(alice(), new_stream().token_account_id, 10, vec![request])
+            .is_err());
+    }
+
+    // Bob requires acceptance of incoming streams, the token takes
+    // 2 tokens of commission on create, refunded on reject.
+    fn new_contract_with_pending_streams() -> Contract {
+        let mut contract = new_contract_with_payment_token();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(dao_id())
+            .attached_deposit(1)
+            .build());
+        let mut token = contract.dao.get_token(&new_stream().token_account_id);
+        token.commission_on_create = 2;
+        contract.dao_update_token(token).unwrap();
+        contract
+            .dao_update_commission_refund_on_reject(true)
+            .unwrap();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .attached_deposit(1)
+            .build());
+        contract.account_update_accept_flag(true).unwrap();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(new_stream().token_account_id)
+            .build());
+        contract
+    }
+
+    #[test]
+    fn test_accept_pending_stream() {
+        let mut contract = new_contract_with_pending_streams();
+        let stream_id = contract
+            .create_streams_op(
+                alice(),
+                new_stream().token_account_id,
+                12,
+                vec![create_request(bob(), Some(12))],
+            )
+            .unwrap()[0];
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.balance, 10);
+        assert_eq!(
+            stream.status,
+            StreamStatus::Pending {
+                is_auto_start_enabled: true,
+                commission: 2,
+            }
+        );
+
+        assert_eq!(
+            contract.accept_stream_op(&alice(), stream_id),
+            Err(ContractError::CallerIsNotStreamReceiver {
+                expected: bob(),
+                received: alice(),
+            })
+        );
+        assert!(matches!(
+            contract.start_stream_op(&alice(), stream_id),
+            Err(ContractError::CannotStartStream { .. })
+        ));
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .block_timestamp(5 * TICKS_PER_SECOND)
+            .build());
+        contract.accept_stream_op(&bob(), stream_id).unwrap();
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.status, StreamStatus::Active);
+        assert_eq!(stream.last_action, 5 * TICKS_PER_SECOND);
+        assert_eq!(
+            contract.reject_stream_op(&bob(), stream_id).err(),
+            Some(ContractError::StreamNotPending {
+                stream_status: StreamStatus::Active
+            })
+        );
+    }
+
+    #[test]
+    fn test_reject_pending_stream() {
+        let mut contract = new_contract_with_pending_streams();
+        let stream_id = contract
+            .create_streams_op(
+                alice(),
+                new_stream().token_account_id,
+                12,
+                vec![create_request(bob(), Some(12))],
+            )
+            .unwrap()[0];
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .build());
+        // Both the balance and the commission are refunded
+        let promises = contract.reject_stream_op(&bob(), stream_id).unwrap();
+        assert_eq!(promises.len(), 2);
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.balance, 0);
+        assert_eq!(
+            stream.status,
+            StreamStatus::Finished {
+                reason: StreamFinishReason::RejectedByReceiver
+            }
+        );
+        let stats: Stats = contract.stats.get().unwrap().into();
+        let token_stats = &stats.dao_tokens[&new_stream().token_account_id];
+        assert_eq!(token_stats.total_commission_collected, 0);
+        assert_eq!(token_stats.refunded, 12);
+        assert_eq!(token_stats.tvl, 0);
+    }
+
+    #[test]
+    fn test_reject_pending_stream_refund_to_creator() {
+        let mut contract = new_contract_with_pending_streams();
+        let token_id: AccountId = "nonpayment.near".parse().unwrap();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(carol())
+            .attached_deposit(DEFAULT_COMMISSION_NON_PAYMENT_FT)
+            .build());
+        contract.account_deposit_near().unwrap();
+
+        // Carol creates the stream on behalf of Alice and pays the commission
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(token_id.clone())
+            .build());
+        contract
+            .create_stream_op(carol(), token_id, 10, create_request(bob(), None))
+            .unwrap();
+        let stream_id = contract
+            .view_account(&carol(), false)
+            .unwrap()
+            .last_created_stream
+            .unwrap();
+        assert_eq!(contract.view_account(&carol(), false).unwrap().deposit, 0);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .build());
+        contract.reject_stream_op(&bob(), stream_id).unwrap();
+        assert_eq!(
+            contract.view_account(&carol(), false).unwrap().deposit,
+            DEFAULT_COMMISSION_NON_PAYMENT_FT
+        );
+        assert_eq!(contract.view_account(&alice(), false).unwrap().deposit, 0);
+    }
+
+    #[test]
+    fn test_keeper_tips() {
+        let mut contract = new_contract_with_payment_token();
+        let mut request = create_request(bob(), Some(100_000));
+        request.tokens_per_sec = U128(1_000);
+        let stream_id = contract
+            .create_streams_op(
+                alice(),
+                new_stream().token_account_id,
+                100_000,
+                vec![request],
+            )
+            .unwrap()[0];
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .attached_deposit(1)
+            .build());
+        contract.account_update_cron_flag(true).unwrap();
+        assert_eq!(
+            contract.account_update_keeper_tip(Some(KeeperTip::Share { bps: 101 })),
+            Err(ContractError::ExceededMaxKeeperTip {
+                max_bps: MAX_KEEPER_TIP_BPS,
+                max_flat: MAX_KEEPER_TIP_FLAT,
+            })
+        );
+        contract
+            .account_update_keeper_tip(Some(KeeperTip::Share { bps: 100 }))
+            .unwrap();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(carol())
+            .block_timestamp(10 * TICKS_PER_SECOND)
+            .build());
+        // 1% of the payout is paid to the keeper
+        let promises = contract.withdraw_op(&carol(), stream_id).unwrap();
+        assert_eq!(promises.len(), 2);
+        let receiver = contract.view_account(&bob(), true).unwrap();
+        assert_eq!(
+            receiver.total_received[&new_stream().token_account_id],
+            9_900
+        );
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .attached_deposit(ONE_NEAR / 10)
+            .build());
+        contract.account_deposit_near().unwrap();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .attached_deposit(1)
+            .build());
+        contract
+            .account_update_keeper_tip(Some(KeeperTip::Flat {
+                amount: MAX_KEEPER_TIP_FLAT,
+            }))
+            .unwrap();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(carol())
+            .block_timestamp(20 * TICKS_PER_SECOND)
+            .build());
+        let promises = contract.withdraw_op(&carol(), stream_id).unwrap();
+        assert_eq!(promises.len(), 2);
+        let receiver = contract.view_account(&bob(), true).unwrap();
+        assert_eq!(receiver.deposit, ONE_NEAR / 10 - MAX_KEEPER_TIP_FLAT);
+        assert_eq!(
+            receiver.total_received[&new_stream().token_account_id],
+            19_900
+        );
+
+        // No tip if the receiver withdraws
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .block_timestamp(30 * TICKS_PER_SECOND)
+            .build());
+        let promises = contract.withdraw_op(&bob(), stream_id).unwrap();
+        assert_eq!(promises.len(), 1);
+
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(35 * TICKS_PER_SECOND)
+            .build());
+        assert_eq!(
+            contract
+                .get_withdrawable_streams(U128(5_000), None, None)
+                .len(),
+            1
+        );
+        assert!(contract
+            .get_withdrawable_streams(U128(5_001), None, None)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_keeper_tip_to_expired_operator() {
+        let mut contract = new_contract_with_payment_token();
+        let mut request = create_request(bob(), Some(100_000));
+        request.tokens_per_sec = U128(1_000);
+        let stream_id = contract
+            .create_streams_op(
+                alice(),
+                new_stream().token_account_id,
+                100_000,
+                vec![request],
+            )
+            .unwrap()[0];
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .attached_deposit(1)
+            .build());
+        contract.account_update_cron_flag(true).unwrap();
+        contract
+            .account_update_keeper_tip(Some(KeeperTip::Share { bps: 100 }))
+            .unwrap();
+        contract
+            .add_operator_op(
+                &bob(),
+                carol(),
+                Operator {
+                    actions: vec![OperatorAction::Withdraw],
+                    stream_id: None,
+                    token_account_id: None,
+                    expires_at: Some(15 * TICKS_PER_SECOND),
+                },
+            )
+            .unwrap();
+
+        // No tip while the operator acts on behalf of the receiver
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(carol())
+            .block_timestamp(10 * TICKS_PER_SECOND)
+            .build());
+        let promises = contract.withdraw_op(&carol(), stream_id).unwrap();
+        assert_eq!(promises.len(), 1);
+
+        // The expired operator withdraws as any keeper does
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(carol())
+            .block_timestamp(20 * TICKS_PER_SECOND)
+            .build());
+        let promises = contract.withdraw_op(&carol(), stream_id).unwrap();
+        assert_eq!(promises.len(), 2);
+        let receiver = contract.view_account(&bob(), true).unwrap();
+        assert_eq!(
+            receiver.total_received[&new_stream().token_account_id],
+            19_900
+        );
+    }
+
+    #[test]
+    fn test_payout_route() {
+        let mut contract = new_contract_with_payment_token();
+        let stream_id = contract
+            .create_streams_op(
+                alice(),
+                new_stream().token_account_id,
+                100,
+                vec![create_request(bob(), Some(100))],
+            )
+            .unwrap()[0];
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .attached_deposit(1)
+            .build());
+        assert_eq!(
+            contract.account_update_payout_route(Some(PayoutRoute::Restream {
+                receiver_id: bob(),
+                tokens_per_sec: U128(1),
+            })),
+            Err(ContractError::InvalidPayoutRoute)
+        );
+        assert!(matches!(
+            contract.account_update_payout_route(Some(PayoutRoute::Restream {
+                receiver_id: carol(),
+                tokens_per_sec: U128(0),
+            })),
+            Err(ContractError::InvalidStreamingSpeed { .. })
+        ));
+        let route = PayoutRoute::Restream {
+            receiver_id: carol(),
+            tokens_per_sec: U128(1),
+        };
+        contract
+            .account_update_payout_route(Some(route.clone()))
+            .unwrap();
+        assert_eq!(
+            contract.view_account(&bob(), true).unwrap().payout_route,
+            Some(route)
+        );
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .block_timestamp(10 * TICKS_PER_SECOND)
+            .build());
+        let promises = contract.withdraw_op(&bob(), stream_id).unwrap();
+        assert_eq!(promises.len(), 1);
+        let receiver = contract.view_account(&bob(), true).unwrap();
+        assert_eq!(receiver.total_received[&new_stream().token_account_id], 10);
+    }
+
+    #[test]
+    fn test_chained_streams() {
+        let mut contract = new_contract_with_payment_token();
+        let token_id = new_stream().token_account_id;
+        let parent_id = contract
+            .create_streams_op(
+                alice(),
+                token_id.clone(),
+                100,
+                vec![create_request(bob(), Some(100))],
+            )
+            .unwrap()[0];
+        let mut request = create_request(carol(), Some(10));
+        request.owner_id = bob();
+        let child_id = contract
+            .create_streams_op(bob(), token_id.clone(), 10, vec![request])
+            .unwrap()[0];
+
+        assert_eq!(
+            contract.chain_stream_op(&carol(), parent_id, Some(child_id)),
+            Err(ContractError::CallerIsNotStreamReceiver {
+                expected: bob(),
+                received: carol(),
+            })
+        );
+        assert_eq!(
+            contract.chain_stream_op(&bob(), parent_id, Some(parent_id)),
+            Err(ContractError::CallerIsNotStreamOwner {
+                expected: alice(),
+                received: bob(),
+            })
+        );
+        contract
+            .chain_stream_op(&bob(), parent_id, Some(child_id))
+            .unwrap();
+        let child = contract.view_stream(&child_id).unwrap();
+        assert_eq!(child.parent_stream_id, Some(parent_id.into()));
+
+        // The payment stays in finance contract
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .block_timestamp(5 * TICKS_PER_SECOND)
+            .build());
+        let promises = contract.withdraw_op(&bob(), parent_id).unwrap();
+        assert!(promises.is_empty());
+        let child = contract.view_stream(&child_id).unwrap();
+        assert_eq!(child.balance, 15);
+        let stats: Stats = contract.stats.get().unwrap().into();
+        assert_eq!(stats.dao_tokens[&token_id].tvl, 110);
+
+        // Finished child cannot be deposited, so the payment is transferred
+        contract.stop_stream_op(&bob(), child_id).unwrap();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .block_timestamp(6 * TICKS_PER_SECOND)
+            .build());
+        let promises = contract.withdraw_op(&bob(), parent_id).unwrap();
+        assert_eq!(promises.len(), 1);
+
+        let chain = contract.get_stream_chain(child_id.into()).unwrap();
+        assert_eq!(
+            chain.iter().map(|stream| stream.id).collect::<Vec<_>>(),
+            vec![parent_id, child_id]
+        );
+    }
+
+    #[test]
+    fn test_chained_stream_debt() {
+        let mut contract = new_contract_with_payment_token();
+        let token_id = new_stream().token_account_id;
+        let parent_id = contract
+            .create_streams_op(
+                alice(),
+                token_id.clone(),
+                100,
+                vec![create_request(bob(), Some(100))],
+            )
+            .unwrap()[0];
+        let mut request = create_request(carol(), Some(10));
+        request.owner_id = bob();
+        request.is_expirable = Some(false);
+        let child_id = contract
+            .create_streams_op(bob(), token_id.clone(), 10, vec![request])
+            .unwrap()[0];
+        contract
+            .chain_stream_op(&bob(), parent_id, Some(child_id))
+            .unwrap();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .block_timestamp(15 * TICKS_PER_SECOND)
+            .build());
+        assert_eq!(contract.view_stream(&child_id).unwrap().debt, 5);
+
+        // The payment settles the debt of the child to its receiver first
+        let promises = contract.withdraw_op(&bob(), parent_id).unwrap();
+        assert_eq!(promises.len(), 2);
+        let child = contract.view_stream(&child_id).unwrap();
+        assert_eq!(child.debt, 0);
+        assert_eq!(child.insolvent_since, None);
+        assert_eq!(child.balance, 10);
+        let receiver = contract.view_account(&carol(), true).unwrap();
+        assert_eq!(receiver.total_received[&token_id], 15);
+        let stats: Stats = contract.stats.get().unwrap().into();
+        assert_eq!(stats.dao_tokens[&token_id].tvl, 95);
+    }
+
+    #[test]
+    fn test_chained_stream_nft_transfer() {
+        let mut contract = new_contract_with_payment_token();
+        let token_id = new_stream().token_account_id;
+        let parent_id = contract
+            .create_streams_op(
+                alice(),
+                token_id.clone(),
+                100,
+                vec![create_request(bob(), Some(100))],
+            )
+            .unwrap()[0];
+        let mut request = create_request(carol(), Some(10));
+        request.owner_id = bob();
+        let child_id = contract
+            .create_streams_op(bob(), token_id, 10, vec![request])
+            .unwrap()[0];
+        contract
+            .chain_stream_op(&bob(), parent_id, Some(child_id))
+            .unwrap();
+        contract
+            .storage_deposit_op(&bob(), ONE_NEAR, false)
+            .unwrap();
+        contract.account_deposit(bob(), ONE_NEAR).unwrap();
+        let nft_token_id = contract.mint_stream_nft_op(&bob(), parent_id).unwrap();
+
+        // The payment before the transfer goes to the child
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(5 * TICKS_PER_SECOND)
+            .build());
+        contract
+            .nft_transfer_op(&bob(), dao_id(), &nft_token_id, None, None)
+            .unwrap();
+        let parent = contract.view_stream(&parent_id).unwrap();
+        assert_eq!(parent.child_stream_id, None);
+        let child = contract.view_stream(&child_id).unwrap();
+        assert_eq!(child.parent_stream_id, None);
+        assert_eq!(child.balance, 15);
+
+        // The new receiver is paid instead of the child of the previous one
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(dao_id())
+            .block_timestamp(10 * TICKS_PER_SECOND)
+            .build());
+        let promises = contract.withdraw_op(&dao_id(), parent_id).unwrap();
+        assert_eq!(promises.len(), 1);
+        let child = contract.view_stream(&child_id).unwrap();
+        assert_eq!(child.balance, 15);
+        let receiver = contract.view_account(&dao_id(), true).unwrap();
+        assert_eq!(receiver.total_received[&new_stream().token_account_id], 5);
+    }
+
+    #[test]
+    fn test_non_expirable_stream_debt() {
+        let mut contract = new_contract_with_payment_token();
+        let token_id = new_stream().token_account_id;
+        let mut request = create_request(bob(), Some(10));
+        request.is_expirable = Some(false);
+        let stream_id = contract
+            .create_streams_op(alice(), token_id.clone(), 10, vec![request])
+            .unwrap()[0];
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .block_timestamp(15 * TICKS_PER_SECOND)
+            .build());
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.debt, 5);
+        assert_eq!(stream.insolvent_since, Some(10 * TICKS_PER_SECOND));
+
+        // The stream keeps running on credit
+        contract.withdraw_op(&bob(), stream_id).unwrap();
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.status, StreamStatus::Active);
+        assert_eq!(stream.balance, 0);
+        assert_eq!(stream.debt, 5);
+
+        // Deposits pay the debt first
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(token_id.clone())
+            .block_timestamp(17 * TICKS_PER_SECOND)
+            .build());
+        contract.deposit_op(token_id.clone(), stream_id, 3).unwrap();
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.balance, 0);
+        assert_eq!(stream.debt, 4);
+        assert_eq!(stream.insolvent_since, Some(10 * TICKS_PER_SECOND));
+
+        contract
+            .deposit_op(token_id.clone(), stream_id, 20)
+            .unwrap();
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.balance, 16);
+        assert_eq!(stream.debt, 0);
+        assert_eq!(stream.insolvent_since, None);
+        assert_eq!(stream.tokens_total_withdrawn, 17);
+        let receiver = contract.view_account(&bob(), true).unwrap();
+        assert_eq!(receiver.total_received[&token_id], 17);
+        let stats: Stats = contract.stats.get().unwrap().into();
+        assert_eq!(stats.dao_tokens[&token_id].tvl, 16);
+    }
+
+    #[test]
+    fn test_cancel_policy() {
+        let mut contract = new_contract_with_payment_token();
+        let token_id = new_stream().token_account_id;
+        let policy = CancelPolicy {
+            no_stop_before: Some(10 * TICKS_PER_SECOND),
+            notice_period_sec: Some(5),
+            severance: 20,
+        };
+        let mut request = create_request(bob(), Some(100));
+        request.is_locked = Some(true);
+        request.cancel_policy = Some(policy.clone());
+        assert_eq!(
+            contract
+                .create_streams_op(alice(), token_id.clone(), 100, vec![request])
+                .err(),
+            Some(ContractError::InvalidCancelPolicy)
+        );
+        let mut request = create_request(bob(), Some(100));
+        request.cancel_policy = Some(policy);
+        let stream_id = contract
+            .create_streams_op(alice(), token_id.clone(), 100, vec![request])
+            .unwrap()[0];
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .block_timestamp(5 * TICKS_PER_SECOND)
+            .build());
+        assert_eq!(
+            contract.stop_stream_op(&alice(), stream_id).err(),
+            Some(ContractError::StopNotAllowedUntil {
+                timestamp: 10 * TICKS_PER_SECOND
+            })
+        );
+
+        // The stop is scheduled after the notice period
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .block_timestamp(10 * TICKS_PER_SECOND)
+            .build());
+        let promises = contract.stop_stream_op(&alice(), stream_id).unwrap();
+        assert!(promises.is_empty());
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.status, StreamStatus::Active);
+        assert_eq!(stream.stop_at, Some(15 * TICKS_PER_SECOND));
+        assert_eq!(
+            contract.stop_stream_op(&alice(), stream_id).err(),
+            Some(ContractError::StopScheduled {
+                stop_at: 15 * TICKS_PER_SECOND
+            })
+        );
+        assert_eq!(
+            contract.pause_stream_op(&bob(), stream_id).err(),
+            Some(ContractError::StopScheduled {
+                stop_at: 15 * TICKS_PER_SECOND
+            })
+        );
+
+        // Nothing is accrued after the notice period, the severance is paid on stop
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .block_timestamp(20 * TICKS_PER_SECOND)
+            .build());
+        let promises = contract.withdraw_op(&bob(), stream_id).unwrap();
+        assert_eq!(promises.len(), 3);
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(
+            stream.status,
+            StreamStatus::Finished {
+                reason: StreamFinishReason::StoppedByOwner
+            }
+        );
+        assert_eq!(stream.tokens_total_withdrawn, 35);
+        assert_eq!(stream.balance, 0);
+        let receiver = contract.view_account(&bob(), true).unwrap();
+        assert_eq!(receiver.total_received[&token_id], 35);
+    }
+
+    #[test]
+    fn test_freeze_and_clawback() {
+        let mut contract = new_contract_with_payment_token();
+        let token_id = new_stream().token_account_id;
+        let opted_out_id = contract
+            .create_streams_op(
+                alice(),
+                token_id.clone(),
+                100,
+                vec![create_request(bob(), Some(100))],
+            )
+            .unwrap()[0];
+        let mut request = create_request(bob(), Some(100));
+        request.is_clawback_allowed = Some(true);
+        let stream_id = contract
+            .create_streams_op(alice(), token_id.clone(), 100, vec![request])
+            .unwrap()[0];
+
+        assert_eq!(
+            contract.freeze_stream_op(opted_out_id),
+            Err(ContractError::ClawbackNotAllowed {
+                stream_id: opted_out_id
+            })
+        );
+
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(10 * TICKS_PER_SECOND)
+            .build());
+        contract.freeze_stream_op(stream_id).unwrap();
+
+        // Nothing is accrued while frozen
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(20 * TICKS_PER_SECOND)
+            .build());
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.available_to_withdraw(), 10);
+        assert_eq!(
+            contract.stop_stream_op(&bob(), stream_id).err(),
+            Some(ContractError::StreamFrozen { stream_id })
+        );
+
+        contract.unfreeze_stream_op(stream_id).unwrap();
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(25 * TICKS_PER_SECOND)
+            .build());
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.available_to_withdraw(), 15);
+
+        // Tokens accrued are returned to the owner as well
+        let promises = contract.clawback_stream_op(stream_id, true).unwrap();
+        assert_eq!(promises.len(), 1);
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(
+            stream.status,
+            StreamStatus::Finished {
+                reason: StreamFinishReason::ClawedBack
+            }
+        );
+        assert_eq!(stream.tokens_total_withdrawn, 0);
+        assert_eq!(stream.balance, 0);
+        let stats: Stats = contract.stats.get().unwrap().into();
+        assert_eq!(stats.dao_tokens[&token_id].tvl, 100);
+    }
+
+    #[test]
+    fn test_dispute_resolution() {
+        let mut contract = new_contract_with_payment_token();
+        let token_id = new_stream().token_account_id;
+        let mut request = create_request(bob(), Some(100));
+        request.arbiter_id = Some(alice());
+        assert_eq!(
+            contract
+                .create_streams_op(alice(), token_id.clone(), 100, vec![request])
+                .err(),
+            Some(ContractError::InvalidArbiter)
+        );
+        let mut request = create_request(bob(), Some(100));
+        request.arbiter_id = Some(carol());
+        let stream_id = contract
+            .create_streams_op(alice(), token_id.clone(), 100, vec![request])
+            .unwrap()[0];
+
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(10 * TICKS_PER_SECOND)
+            .build());
+        assert_eq!(
+            contract.open_dispute_op(&carol(), stream_id),
+            Err(ContractError::CallerIsNotStreamActor {
+                owner: alice(),
+                receiver: bob(),
+                caller: carol(),
+            })
+        );
+        contract.open_dispute_op(&bob(), stream_id).unwrap();
+        let owner = contract.view_account(&alice(), true).unwrap();
+        assert_eq!(owner.total_outgoing[&token_id], 0);
+
+        // Nothing is accrued or stopped while disputed
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(20 * TICKS_PER_SECOND)
+            .build());
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.available_to_withdraw(), 0);
+        let disputed = StreamStatus::Disputed { opened_by: bob() };
+        assert_eq!(stream.status, disputed);
+        assert_eq!(
+            contract.stop_stream_op(&alice(), stream_id).err(),
+            Some(ContractError::CannotStopStream {
+                stream_status: disputed
+            })
+        );
+
+        assert_eq!(
+            contract.resolve_dispute_op(&bob(), stream_id, 30).err(),
+            Some(ContractError::CallerIsNotStreamArbiter {
+                expected: Some(carol()),
+                received: bob(),
+            })
+        );
+        assert_eq!(
+            contract.resolve_dispute_op(&carol(), stream_id, 101).err(),
+            Some(ContractError::InvalidDisputeSplit { balance: 100 })
+        );
+        let promises = contract
+            .resolve_dispute_op(&carol(), stream_id, 30)
+            .unwrap();
+        assert_eq!(promises.len(), 2);
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(
+            stream.status,
+            StreamStatus::Finished {
+                reason: StreamFinishReason::ResolvedByArbiter
+            }
+        );
+        assert_eq!(stream.tokens_total_withdrawn, 30);
+        assert_eq!(stream.balance, 0);
+        let receiver = contract.view_account(&bob(), true).unwrap();
+        assert_eq!(receiver.total_received[&token_id], 30);
+        let stats: Stats = contract.stats.get().unwrap().into();
+        assert_eq!(stats.dao_tokens[&token_id].tvl, 0);
+    }
+
+    #[test]
+    fn test_split_and_merge_streams() {
+        let mut contract = new_contract_with_payment_token();
+        let token_id = new_stream().token_account_id;
+        let mut request = create_request(bob(), Some(100));
+        request.tokens_per_sec = U128(10);
+        let stream_id = contract
+            .create_streams_op(alice(), token_id.clone(), 100, vec![request])
+            .unwrap()[0];
+
+        // Tokens accrued are paid, the rest is split
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(2 * TICKS_PER_SECOND)
+            .build());
+        assert_eq!(
+            contract.split_stream_op(&bob(), stream_id, 10_000),
+            Err(ContractError::InvalidSplitRatio)
+        );
+        let split_id = contract.split_stream_op(&bob(), stream_id, 2_500).unwrap();
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.balance, 60);
+        assert_eq!(stream.tokens_per_sec, 8);
+        assert_eq!(stream.tokens_total_withdrawn, 20);
+        let split = contract.view_stream(&split_id).unwrap();
+        assert_eq!(split.status, StreamStatus::Active);
+        assert_eq!(split.balance, 20);
+        assert_eq!(split.tokens_per_sec, 2);
+        assert_eq!(split.creator_id, bob());
+        let owner = contract.view_account(&alice(), true).unwrap();
+        assert_eq!(owner.total_outgoing[&token_id], 10);
+        let stats: Stats = contract.stats.get().unwrap().into();
+        assert_eq!(stats.dao_tokens[&token_id].tvl, 80);
+        assert_eq!(stats.dao_tokens[&token_id].active_streams, 2);
+
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(5 * TICKS_PER_SECOND)
+            .build());
+        assert_eq!(
+            contract.merge_streams_op(&alice(), vec![stream_id]).err(),
+            Some(ContractError::InvalidMergedStreams {
+                max_merged_streams: MAX_MERGED_STREAMS
+            })
+        );
+        assert_eq!(
+            contract
+                .merge_streams_op(&bob(), vec![stream_id, split_id])
+                .err(),
+            Some(ContractError::CallerIsNotStreamOwner {
+                expected: alice(),
+                received: bob(),
+            })
+        );
+        assert_eq!(
+            contract
+                .merge_streams_op(&alice(), vec![stream_id, stream_id])
+                .err(),
+            Some(ContractError::CannotMergeStreams { stream_id })
+        );
+        let promises = contract
+            .merge_streams_op(&alice(), vec![stream_id, split_id])
+            .unwrap();
+        assert_eq!(promises.len(), 2);
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.status, StreamStatus::Active);
+        assert_eq!(stream.balance, 50);
+        assert_eq!(stream.tokens_per_sec, 10);
+        let split = contract.view_stream(&split_id).unwrap();
+        assert_eq!(
+            split.status,
+            StreamStatus::Finished {
+                reason: StreamFinishReason::MergedIntoStream
+            }
+        );
+        assert_eq!(split.balance, 0);
+        let receiver = contract.view_account(&bob(), true).unwrap();
+        assert_eq!(receiver.total_incoming[&token_id], 10);
+        assert_eq!(receiver.total_received[&token_id], 50);
+        let stats: Stats = contract.stats.get().unwrap().into();
+        assert_eq!(stats.dao_tokens[&token_id].tvl, 50);
+        assert_eq!(stats.dao_tokens[&token_id].active_streams, 1);
+    }
+
+    #[test]
+    fn test_split_paused_stream() {
+        let mut contract = new_contract_with_payment_token();
+        let token_id = new_stream().token_account_id;
+        let mut request = create_request(bob(), Some(100));
+        request.tokens_per_sec = U128(10);
+        let stream_id = contract
+            .create_streams_op(alice(), token_id.clone(), 100, vec![request])
+            .unwrap()[0];
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(2 * TICKS_PER_SECOND)
+            .build());
+        contract.pause_stream_op(&alice(), stream_id).unwrap();
+
+        let split_id = contract.split_stream_op(&bob(), stream_id, 2_500).unwrap();
+        let split = contract.view_stream(&split_id).unwrap();
+        assert_eq!(split.status, StreamStatus::Paused);
+        assert_eq!(split.balance, 20);
+        let owner = contract.view_account(&alice(), true).unwrap();
+        assert!(owner.inactive_outgoing_streams.contains(&split_id));
+        assert_eq!(owner.total_outgoing[&token_id], 0);
+        let receiver = contract.view_account(&bob(), true).unwrap();
+        assert!(receiver.inactive_incoming_streams.contains(&split_id));
+        assert_eq!(contract.stream_status_counts.paused, 2);
+        assert_eq!(contract.stream_status_counts.initialized, 0);
+
+        // Paused streams are merged back
+        contract
+            .merge_streams_op(&alice(), vec![stream_id, split_id])
+            .unwrap();
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.status, StreamStatus::Paused);
+        assert_eq!(stream.balance, 80);
+        assert_eq!(stream.tokens_per_sec, 10);
+    }
+
+    #[test]
+    fn test_merge_streams_incompatible() {
+        let mut contract = new_contract_with_payment_token();
+        let token_id = new_stream().token_account_id;
+        let create_pair = |contract: &mut Contract, request: CreateRequest| {
+            let stream_ids = contract
+                .create_streams_op(
+                    alice(),
+                    token_id.clone(),
+                    200,
+                    vec![create_request(bob(), Some(100)), request],
+                )
+                .unwrap();
+            (stream_ids[0], stream_ids[1])
+        };
+
+        let mut request = create_request(bob(), Some(100));
+        request.is_clawback_allowed = Some(true);
+        let (stream_id, merged_id) = create_pair(&mut contract, request);
+        assert_eq!(
+            contract
+                .merge_streams_op(&alice(), vec![stream_id, merged_id])
+                .err(),
+            Some(ContractError::CannotMergeStreams {
+                stream_id: merged_id
+            })
+        );
+
+        let mut request = create_request(bob(), Some(100));
+        request.arbiter_id = Some(carol());
+        let (stream_id, merged_id) = create_pair(&mut contract, request);
+        assert_eq!(
+            contract
+                .merge_streams_op(&alice(), vec![stream_id, merged_id])
+                .err(),
+            Some(ContractError::CannotMergeStreams {
+                stream_id: merged_id
+            })
+        );
+
+        let mut request = create_request(bob(), Some(100));
+        request.cancel_policy = Some(CancelPolicy {
+            no_stop_before: None,
+            notice_period_sec: None,
+            severance: 10,
+        });
+        let (stream_id, merged_id) = create_pair(&mut contract, request);
+        assert_eq!(
+            contract
+                .merge_streams_op(&alice(), vec![stream_id, merged_id])
+                .err(),
+            Some(ContractError::CannotMergeStreams {
+                stream_id: merged_id
+            })
+        );
+
+        let mut request = create_request(bob(), Some(100));
+        request.approver_id = Some(carol());
+        request.milestones = Some(vec![U128(50)]);
+        let (stream_id, merged_id) = create_pair(&mut contract, request);
+        assert_eq!(
+            contract
+                .merge_streams_op(&alice(), vec![stream_id, merged_id])
+                .err(),
+            Some(ContractError::CannotMergeStreams {
+                stream_id: merged_id
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_streams_pledged_or_chained() {
+        let mut contract = new_contract_with_payment_token();
+        let stream_ids = contract
+            .create_streams_op(
+                alice(),
+                new_stream().token_account_id,
+                300,
+                vec![
+                    create_request(bob(), Some(100)),
+                    create_request(bob(), Some(100)),
+                    create_request(bob(), Some(100)),
+                ],
+            )
+            .unwrap();
+        let (stream_id, merged_id) = (stream_ids[0], stream_ids[1]);
+        let update = |contract: &mut Contract, f: &dyn Fn(&mut Stream)| {
+            let mut stream = contract.extract_stream(&merged_id).unwrap();
+            f(&mut stream);
+            contract.save_stream(stream).unwrap();
+        };
+
+        update(&mut contract, &|stream| {
+            stream.pledge = Some(Pledge {
+                lender_id: carol(),
+                amount: 10,
+                repaid: 0,
+                share_bps: 5_000,
+            })
+        });
+        assert_eq!(
+            contract
+                .merge_streams_op(&alice(), vec![stream_id, merged_id])
+                .err(),
+            Some(ContractError::StreamPledged {
+                stream_id: merged_id
+            })
+        );
+        assert_eq!(
+            contract
+                .merge_streams_op(&alice(), vec![merged_id, stream_id])
+                .err(),
+            Some(ContractError::StreamPledged {
+                stream_id: merged_id
+            })
+        );
+
+        update(&mut contract, &|stream| {
+            stream.pledge = None;
+            stream.child_stream_id = Some(stream_ids[2].into());
+        });
+        assert_eq!(
+            contract
+                .merge_streams_op(&alice(), vec![stream_id, merged_id])
+                .err(),
+            Some(ContractError::CannotMergeStreams {
+                stream_id: merged_id
+            })
+        );
+
+        update(&mut contract, &|stream| {
+            stream.child_stream_id = None;
+            stream.parent_stream_id = Some(stream_ids[2].into());
+        });
+        assert_eq!(
+            contract
+                .merge_streams_op(&alice(), vec![stream_id, merged_id])
+                .err(),
+            Some(ContractError::CannotMergeStreams {
+                stream_id: merged_id
+            })
+        );
+
+        update(&mut contract, &|stream| stream.parent_stream_id = None);
+        contract
+            .merge_streams_op(&alice(), vec![stream_id, merged_id])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_create_streams_batch() {
+        let mut contract = new_contract_with_payment_token();
+        let stream_ids = contract
+            .create_streams_op(
+                alice(),
+                new_stream().token_account_id,
+                3 * ONE_NEAR,
+                vec![
+                    create_request(bob(), Some(ONE_NEAR)),
+                    create_request(carol(), Some(2 * ONE_NEAR)),
+                ],
+            )
+            .unwrap();
+        assert_eq!(stream_ids.len(), 2);
+        let stream = contract.view_stream(&stream_ids[1]).unwrap();
+        assert_eq!(stream.receiver_id, carol());
+        assert_eq!(stream.balance, 2 * ONE_NEAR);
+        assert_eq!(stream.status, StreamStatus::Active);
+        assert_eq!(
+            contract
+                .view_account(&alice(), true)
+                .unwrap()
+                .total_streams_created,
+            2
+        );
+    }
+
+    #[test]
+    fn test_create_streams_batch_invalid_balance() {
+        let mut contract = new_contract_with_payment_token();
+        assert_eq!(
+            contract.create_streams_op(
+                alice(),
+                new_stream().token_account_id,
+                3 * ONE_NEAR,
+                vec![
+                    create_request(bob(), Some(ONE_NEAR)),
+                    create_request(carol(), Some(ONE_NEAR)),
+                ],
+            ),
+            Err(ContractError::InvalidBatchBalance {
+                expected: 3 * ONE_NEAR,
+                received: 2 * ONE_NEAR,
+            })
+        );
+        assert_eq!(
+            contract.create_streams_op(
+                alice(),
+                new_stream().token_account_id,
+                3 * ONE_NEAR,
+                vec![
+                    create_request(bob(), Some(3 * ONE_NEAR)),
+                    create_request(carol(), None),
+                ],
+            ),
+            Err(ContractError::ZeroTokenTransfer)
+        );
+        assert_eq!(contract.streams.len(), 0);
+    }
+
+    #[test]
+    fn test_batch_ops() {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        let stream_ids = [create_stream(&mut contract), create_stream(&mut contract)];
+        let unknown_id: StreamId = env::sha256(&[1, 2, 3]).as_slice().try_into().unwrap();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(1)
+            .build());
+        let batch = contract
+            .pause_streams(vec![
+                stream_ids[0].into(),
+                unknown_id.into(),
+                stream_ids[1].into(),
+            ])
+            .unwrap();
+        assert!(batch.unprocessed.is_empty());
+        assert_eq!(batch.results.len(), 3);
+        assert_eq!(batch.results[0].result, Ok(()));
+        assert_eq!(
+            batch.results[1].result,
+            Err(ContractError::StreamNotExist {
+                stream_id: unknown_id
+            })
+        );
+        assert_eq!(batch.results[2].stream_id, stream_ids[1]);
+        assert_eq!(batch.results[2].result, Ok(()));
+
+        // Failed validations keep streams untouched
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .attached_deposit(1)
+            .build());
+        let batch = contract
+            .start_streams(stream_ids.iter().map(|&id| id.into()).collect())
+            .unwrap();
+        assert!(batch.results.iter().all(|result| matches!(
+            result.result,
+            Err(ContractError::CallerIsNotStreamOwner { .. })
+        )));
+        for stream_id in stream_ids.iter() {
+            assert_eq!(
+                contract.view_stream(stream_id).unwrap().status,
+                StreamStatus::Paused
+            );
+        }
+    }
+
+    #[test]
+    fn test_batch_ops_insufficient_gas() {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        let stream_ids = [create_stream(&mut contract), create_stream(&mut contract)];
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(1)
+            .prepaid_gas(contract.gas_for_batch(&stream_ids[0], true))
+            .build());
+        let batch = contract
+            .stop_streams(stream_ids.iter().map(|&id| id.into()).collect())
+            .unwrap();
+        assert!(batch.results.is_empty());
+        assert_eq!(
+            batch.unprocessed,
+            stream_ids
+                .iter()
+                .map(|&id| id.into())
+                .collect::<Vec<Base58CryptoHash>>()
+        );
+        assert_eq!(
+            contract.view_stream(&stream_ids[0]).unwrap().status,
+            StreamStatus::Active
+        );
+    }
+
+    #[test]
+    fn test_batch_ops_gas_for_routed_payout() {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        let stream_id = create_stream(&mut contract);
+        assert_eq!(
+            contract.gas_for_batch(&stream_id, false),
+            GAS_FOR_BATCH_START + GAS_FOR_FT_TRANSFER_FROM_FINANCE
+        );
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .attached_deposit(1)
+            .build());
+        contract
+            .account_update_payout_route(Some(PayoutRoute::Account {
+                account_id: carol(),
+            }))
+            .unwrap();
+        assert_eq!(
+            contract.gas_for_batch(&stream_id, false),
+            GAS_FOR_BATCH_START + GAS_FOR_PAYOUT_FROM_FINANCE
+        );
+        // The payout and the refund
+        let gas_needed = contract.gas_for_batch(&stream_id, true);
+        assert_eq!(
+            gas_needed,
+            GAS_FOR_BATCH_START + GAS_FOR_PAYOUT_FROM_FINANCE + GAS_FOR_FT_TRANSFER_FROM_FINANCE
+        );
+
+        // Not enough for the payout, the stream is left untouched
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(1)
+            .prepaid_gas(Gas::ONE_TERA * 70)
+            .build());
+        let batch = contract.stop_streams(vec![stream_id.into()]).unwrap();
+        assert!(batch.results.is_empty());
+        assert_eq!(batch.unprocessed.len(), 1);
+        assert_eq!(
+            contract.view_stream(&stream_id).unwrap().status,
+            StreamStatus::Active
+        );
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(1)
+            .prepaid_gas(gas_needed + Gas::ONE_TERA * 5)
+            .build());
+        let batch = contract.stop_streams(vec![stream_id.into()]).unwrap();
+        assert_eq!(batch.results[0].result, Ok(()));
+        assert_eq!(
+            contract.view_stream(&stream_id).unwrap().status,
+            StreamStatus::Finished {
+                reason: StreamFinishReason::StoppedByOwner
+            }
+        );
+    }
+
+    #[test]
+    fn test_batch_ops_error_after_extraction() {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        let stream_id = create_stream(&mut contract);
+
+        // The stream touched before the error fails the whole batch
+        let batch = contract.batch_op(
+            vec![stream_id],
+            |_, _| GAS_FOR_BATCH_START,
+            |contract, stream_id| {
+                contract.extract_stream(&stream_id)?;
+                Err(ContractError::InsufficientGas {
+                    expected: GAS_FOR_PAYOUT_FROM_FINANCE,
+                    left: Gas(0),
+                })
+            },
+        );
+        assert!(matches!(batch, Err(ContractError::InsufficientGas { .. })));
+    }
+
+    #[test]
+    fn test_stream_nft_transfer() {
+        let mut contract = new_contract_with_payment_token();
+        let token_id = new_stream().token_account_id;
+        let stream_id = contract
+            .create_streams_op(
+                alice(),
+                token_id.clone(),
+                100,
+                vec![create_request(bob(), Some(100))],
+            )
+            .unwrap()[0];
+        contract
+            .storage_deposit_op(&bob(), ONE_NEAR, false)
+            .unwrap();
+        contract
+            .storage_deposit_op(&carol(), ONE_NEAR, false)
+            .unwrap();
+        contract.account_deposit(bob(), ONE_NEAR).unwrap();
+
+        assert_eq!(
+            contract.mint_stream_nft_op(&alice(), stream_id),
+            Err(ContractError::CallerIsNotStreamReceiver {
+                expected: bob(),
+                received: alice(),
+            })
+        );
+        let nft_token_id = contract.mint_stream_nft_op(&bob(), stream_id).unwrap();
+        assert_eq!(nft_token_id, stream_nft_token_id(&stream_id));
+        assert_eq!(
+            contract.mint_stream_nft_op(&bob(), stream_id),
+            Err(ContractError::StreamNftExists { stream_id })
+        );
+        let mint_storage_usage = contract.view_account(&bob(), true).unwrap().storage_usage;
+        assert!(mint_storage_usage > 0);
+
+        // Approved account transfers on behalf of the owner
+        let approval_id = contract
+            .nft_approve_op(&bob(), &nft_token_id, alice())
+            .unwrap();
+        assert!(contract.view_account(&bob(), true).unwrap().storage_usage > mint_storage_usage);
+        assert_eq!(
+            contract
+                .nft_transfer_op(&carol(), carol(), &nft_token_id, None, None)
+                .err(),
+            Some(ContractError::StreamNftNotApproved {
+                account_id: carol()
+            })
+        );
+        assert_eq!(
+            contract
+                .nft_transfer_op(
+                    &alice(),
+                    carol(),
+                    &nft_token_id,
+                    Some(approval_id + 1),
+                    None
+                )
+                .err(),
+            Some(ContractError::StreamNftNotApproved {
+                account_id: alice()
+            })
+        );
+        assert_eq!(
+            contract
+                .nft_transfer_op(&alice(), bob(), &nft_token_id, Some(approval_id), None)
+                .err(),
+            Some(ContractError::InvalidStreamNftReceiver)
+        );
+
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(10 * TICKS_PER_SECOND)
+            .build());
+        let promises = contract
+            .nft_transfer_op(&alice(), carol(), &nft_token_id, Some(approval_id), None)
+            .unwrap();
+        // Payment to the previous receiver and storage deposit of the new one
+        assert_eq!(promises.len(), 2);
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.receiver_id, carol());
+        assert_eq!(stream.tokens_total_withdrawn, 10);
+        let bob_account = contract.view_account(&bob(), true).unwrap();
+        assert_eq!(bob_account.total_received[&token_id], 10);
+        assert_eq!(bob_account.storage_usage, mint_storage_usage);
+        assert_eq!(bob_account.deposit, ONE_NEAR - DEFAULT_STORAGE_BALANCE);
+        assert!(bob_account.active_incoming_streams.is_empty());
+        let carol_account = contract.view_account(&carol(), true).unwrap();
+        assert!(carol_account.active_incoming_streams.contains(&stream_id));
+        assert_eq!(carol_account.total_incoming[&token_id], 1);
+        let (stream, stream_nft) = contract.view_stream_nft(&nft_token_id).unwrap();
+        assert!(stream_nft.approved_account_ids.is_empty());
+        let token = contract.stream_nft_token(&stream, stream_nft);
+        assert_eq!(token.owner_id, carol());
+        assert_eq!(
+            contract
+                .nft_transfer_op(&bob(), bob(), &nft_token_id, None, None)
+                .err(),
+            Some(ContractError::StreamNftNotApproved { account_id: bob() })
+        );
+
+        // The token is burnt with the stream stopped
+        contract.stop_stream_op(&alice(), stream_id).unwrap();
+        assert!(contract.stream_nfts.is_empty());
+        assert_eq!(
+            contract.view_stream_nft(&nft_token_id).err(),
+            Some(ContractError::StreamNftNotExist {
+                token_id: nft_token_id
+            })
+        );
+        assert_eq!(
+            contract.view_account(&bob(), true).unwrap().storage_usage,
+            0
+        );
+    }
+
+    #[test]
+    fn test_stream_nft_transfer_require_accept() {
+        let mut contract = new_contract_with_payment_token();
+        let stream_ids = contract
+            .create_streams_op(
+                alice(),
+                new_stream().token_account_id,
+                200,
+                vec![
+                    create_request(bob(), Some(100)),
+                    create_request(bob(), Some(100)),
+                ],
+            )
+            .unwrap();
+        contract
+            .storage_deposit_op(&bob(), ONE_NEAR, false)
+            .unwrap();
+        contract.account_deposit(bob(), ONE_NEAR).unwrap();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(carol())
+            .attached_deposit(1)
+            .build());
+        contract.account_update_accept_flag(true).unwrap();
+
+        // Carol accepts the stream as if it was created to her
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(10 * TICKS_PER_SECOND)
+            .build());
+        let nft_token_id = contract.mint_stream_nft_op(&bob(), stream_ids[0]).unwrap();
+        contract
+            .nft_transfer_op(&bob(), carol(), &nft_token_id, None, None)
+            .unwrap();
+        let stream = contract.view_stream(&stream_ids[0]).unwrap();
+        assert_eq!(stream.receiver_id, carol());
+        assert_eq!(
+            stream.status,
+            StreamStatus::Pending {
+                is_auto_start_enabled: true,
+                commission: 0,
+            }
+        );
+        assert!(contract.view_stream_nft(&nft_token_id).is_ok());
+
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(20 * TICKS_PER_SECOND)
+            .build());
+        contract.accept_stream_op(&carol(), stream_ids[0]).unwrap();
+        let stream = contract.view_stream(&stream_ids[0]).unwrap();
+        assert_eq!(stream.status, StreamStatus::Active);
+        // Nothing is accrued while pending
+        assert_eq!(stream.balance, 90);
+        assert_eq!(stream.last_action, 20 * TICKS_PER_SECOND);
+
+        // The pending stream is returned running by the resolve of transfer call
+        let nft_token_id = contract.mint_stream_nft_op(&bob(), stream_ids[1]).unwrap();
+        contract
+            .nft_transfer_op(&bob(), carol(), &nft_token_id, None, None)
+            .unwrap();
+        assert_eq!(
+            contract.nft_resolve_transfer_op(bob(), carol(), &nft_token_id),
+            Ok(false)
+        );
+        let stream = contract.view_stream(&stream_ids[1]).unwrap();
+        assert_eq!(stream.receiver_id, bob());
+        assert_eq!(stream.status, StreamStatus::Active);
+    }
+
+    #[test]
+    fn test_stream_nft_transfer_rejected() {
+        let mut contract = new_contract_with_payment_token();
+        let token_id = new_stream().token_account_id;
+        let stream_id = contract
+            .create_streams_op(
+                alice(),
+                token_id.clone(),
+                100,
+                vec![create_request(bob(), Some(100))],
+            )
+            .unwrap()[0];
+        contract
+            .storage_deposit_op(&bob(), ONE_NEAR, false)
+            .unwrap();
+        contract.account_deposit(bob(), ONE_NEAR).unwrap();
+        let nft_token_id = contract.mint_stream_nft_op(&bob(), stream_id).unwrap();
+
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(10 * TICKS_PER_SECOND)
+            .build());
+        contract
+            .nft_transfer_op(&bob(), carol(), &nft_token_id, None, None)
+            .unwrap();
+
+        // Tokens accrued since the transfer are kept for the previous owner
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(15 * TICKS_PER_SECOND)
+            .build());
+        assert_eq!(
+            contract.nft_resolve_transfer_op(bob(), carol(), &nft_token_id),
+            Ok(false)
+        );
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.receiver_id, bob());
+        assert_eq!(stream.tokens_total_withdrawn, 10);
+        let carol_account = contract.view_account(&carol(), true).unwrap();
+        assert!(!carol_account.total_received.contains_key(&token_id));
+        assert!(carol_account.active_incoming_streams.is_empty());
+        assert_eq!(carol_account.total_incoming[&token_id], 0);
+
+        contract.withdraw_op(&bob(), stream_id).unwrap();
+        let bob_account = contract.view_account(&bob(), true).unwrap();
+        assert_eq!(bob_account.total_received[&token_id], 15);
+        assert_eq!(bob_account.total_incoming[&token_id], 1);
+    }
+
+    #[test]
+    fn test_stream_nft_transfer_paused() {
+        let mut contract = new_contract_with_payment_token();
+        let token_id = new_stream().token_account_id;
+        let stream_id = contract
+            .create_streams_op(
+                alice(),
+                token_id.clone(),
+                100,
+                vec![create_request(bob(), Some(100))],
+            )
+            .unwrap()[0];
+        contract
+            .storage_deposit_op(&bob(), ONE_NEAR, false)
+            .unwrap();
+        contract.account_deposit(bob(), ONE_NEAR).unwrap();
+        let nft_token_id = contract.mint_stream_nft_op(&bob(), stream_id).unwrap();
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(10 * TICKS_PER_SECOND)
+            .build());
+        contract.pause_stream_op(&alice(), stream_id).unwrap();
+
+        let promises = contract
+            .nft_transfer_op(&bob(), carol(), &nft_token_id, None, None)
+            .unwrap();
+        // Storage deposit of the new receiver only
+        assert_eq!(promises.len(), 1);
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.receiver_id, carol());
+        assert_eq!(stream.status, StreamStatus::Paused);
+        let bob_account = contract.view_account(&bob(), true).unwrap();
+        assert!(bob_account.inactive_incoming_streams.is_empty());
+        let carol_account = contract.view_account(&carol(), true).unwrap();
+        assert!(carol_account.inactive_incoming_streams.contains(&stream_id));
+
+        // The stream started again runs to the new receiver
+        contract.start_stream_op(&alice(), stream_id).unwrap();
+        let carol_account = contract.view_account(&carol(), true).unwrap();
+        assert!(carol_account.active_incoming_streams.contains(&stream_id));
+        assert_eq!(carol_account.total_incoming[&token_id], 1);
+    }
+
+    #[test]
+    fn test_stream_nft_owner_index() {
+        let mut contract = new_contract_with_payment_token();
+        let stream_ids = contract
+            .create_streams_op(
+                alice(),
+                new_stream().token_account_id,
+                300,
+                vec![
+                    create_request(bob(), Some(100)),
+                    create_request(bob(), Some(100)),
+                    create_request(bob(), Some(100)),
+                ],
+            )
+            .unwrap();
+        contract
+            .storage_deposit_op(&bob(), ONE_NEAR, false)
+            .unwrap();
+        contract.account_deposit(bob(), ONE_NEAR).unwrap();
+        for stream_id in stream_ids.iter() {
+            contract.mint_stream_nft_op(&bob(), *stream_id).unwrap();
+        }
+        // View methods take the contract by value
+        let clone = |contract: &Contract| {
+            Contract::try_from_slice(&contract.try_to_vec().unwrap()).unwrap()
+        };
+        assert_eq!(clone(&contract).nft_supply_for_owner(bob()), U128(3));
+        let tokens = clone(&contract).nft_tokens_for_owner(bob(), Some(U128(1)), Some(5));
+        assert_eq!(tokens.len(), 2);
+        assert!(tokens.iter().all(|token| token.owner_id == bob()));
+
+        let nft_token_id = stream_nft_token_id(&stream_ids[0]);
+        contract
+            .nft_transfer_op(&bob(), carol(), &nft_token_id, None, None)
+            .unwrap();
+        assert_eq!(clone(&contract).nft_supply_for_owner(bob()), U128(2));
+        let tokens = clone(&contract).nft_tokens_for_owner(carol(), None, None);
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].token_id, nft_token_id);
+
+        // Burnt tokens are removed from the index
+        contract.stop_stream_op(&alice(), stream_ids[0]).unwrap();
+        assert!(contract.owner_stream_nfts.get(&carol()).is_none());
+        assert_eq!(clone(&contract).nft_supply_for_owner(carol()), U128(0));
+        assert_eq!(clone(&contract).nft_supply_for_owner(bob()), U128(2));
+    }
+
+    #[test]
+    fn test_stream_pledge() {
+        let mut contract = new_contract_with_payment_token();
+        let token_id = new_stream().token_account_id;
+        let mut request = create_request(bob(), Some(100));
+        request.tokens_per_sec = U128(10);
+        let stream_id = contract
+            .create_streams_op(alice(), token_id.clone(), 100, vec![request])
+            .unwrap()[0];
+
+        assert_eq!(
+            contract.pledge_stream_op(&alice(), stream_id, carol(), 30, 5_000),
+            Err(ContractError::CallerIsNotStreamReceiver {
+                expected: bob(),
+                received: alice(),
+            })
+        );
+        assert_eq!(
+            contract.pledge_stream_op(&bob(), stream_id, carol(), 30, 0),
+            Err(ContractError::InvalidPledge)
+        );
+        contract
+            .pledge_stream_op(&bob(), stream_id, carol(), 30, 5_000)
+            .unwrap();
+        assert_eq!(
+            contract.pledge_stream_op(&bob(), stream_id, alice(), 30, 5_000),
+            Err(ContractError::StreamPledged { stream_id })
+        );
+
+        // Half of the payment repays the pledge
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(2 * TICKS_PER_SECOND)
+            .build());
+        assert_eq!(contract.withdraw_op(&bob(), stream_id).unwrap().len(), 2);
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.tokens_total_withdrawn, 20);
+        assert_eq!(stream.pledge.as_ref().unwrap().repaid, 10);
+        let receiver = contract.view_account(&bob(), true).unwrap();
+        assert_eq!(receiver.total_received[&token_id], 10);
+
+        // No stop or receiver change without the lender
+        assert_eq!(
+            contract.stop_stream_op(&bob(), stream_id).err(),
+            Some(ContractError::StreamPledged { stream_id })
+        );
+        assert_eq!(
+            contract
+                .change_receiver_op(&bob(), stream_id, alice(), 0, 0)
+                .err(),
+            Some(ContractError::StreamPledged { stream_id })
+        );
+        assert_eq!(
+            contract.split_stream_op(&bob(), stream_id, 5_000),
+            Err(ContractError::StreamPledged { stream_id })
+        );
+        assert_eq!(
+            contract.split_stream_op(&alice(), stream_id, 5_000),
+            Err(ContractError::StreamPledged { stream_id })
+        );
+        assert_eq!(
+            contract.release_pledge_op(&bob(), stream_id),
+            Err(ContractError::CallerIsNotLender {
+                expected: carol(),
+                received: bob(),
+            })
+        );
+
+        // The rest of the pledge is repaid and the pledge is released
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(6 * TICKS_PER_SECOND)
+            .build());
+        contract.withdraw_op(&bob(), stream_id).unwrap();
+        let stream = contract.view_stream(&stream_id).unwrap();
+        assert_eq!(stream.tokens_total_withdrawn, 60);
+        assert!(stream.pledge.is_none());
+        let receiver = contract.view_account(&bob(), true).unwrap();
+        assert_eq!(receiver.total_received[&token_id], 30);
+        assert_eq!(
+            contract.release_pledge_op(&carol(), stream_id),
+            Err(ContractError::StreamNotPledged { stream_id })
+        );
+        contract.stop_stream_op(&bob(), stream_id).unwrap();
+    }
 }
@@ -0,0 +1,174 @@
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use crate::*;
+    use near_sdk::test_utils::test_env::{alice, bob, carol};
+    use near_sdk::{test_utils::VMContextBuilder, testing_env, ONE_NEAR};
+
+    const START: Timestamp = 1_000 * TICKS_PER_SECOND;
+
+    fn finance_id() -> AccountId {
+        "finance.near".parse().unwrap()
+    }
+    fn dao_id() -> AccountId {
+        "dao.near".parse().unwrap()
+    }
+    fn utility_token_id() -> AccountId {
+        "utilitytoken.near".parse().unwrap()
+    }
+    fn token_id() -> AccountId {
+        "token.near".parse().unwrap()
+    }
+
+    fn set_context(predecessor_id: AccountId, timestamp: Timestamp) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor_id)
+            .block_timestamp(timestamp)
+            .build());
+    }
+
+    // Alice is subscribed to Bob for 100 tokens every 30 seconds
+    // and has 250 tokens in treasury.
+    fn new_contract() -> (Contract, SubscriptionId) {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(dao_id())
+            .attached_deposit(1)
+            .build());
+        let mut token = Token::new_unlisted(&token_id());
+        token.is_payment = true;
+        token.commission_coef = SafeFloat { val: 1, pow: -2 };
+        contract.dao_update_token(token).unwrap();
+
+        set_context(token_id(), START);
+        contract
+            .storage_deposit_op(&alice(), ONE_NEAR, false)
+            .unwrap();
+        contract
+            .fund_treasury_op(&alice(), token_id(), 250)
+            .unwrap();
+        let subscription_id = contract
+            .create_subscription_op(&alice(), bob(), token_id(), 100, 30)
+            .unwrap();
+        (contract, subscription_id)
+    }
+
+    #[test]
+    fn test_charge_subscription() {
+        let (mut contract, subscription_id) = new_contract();
+        let subscription = contract.view_subscription(&subscription_id).unwrap();
+        assert!(subscription.storage_usage > 0);
+        assert_eq!(
+            contract.view_account(&alice(), true).unwrap().storage_usage,
+            subscription.storage_usage
+        );
+        assert!(contract
+            .view_account(&bob(), true)
+            .unwrap()
+            .incoming_subscriptions
+            .contains(&subscription_id));
+
+        set_context(bob(), START);
+        assert!(contract
+            .charge_subscription_op(&bob(), subscription_id, None)
+            .unwrap()
+            .is_some());
+        let subscription = contract.view_subscription(&subscription_id).unwrap();
+        assert_eq!(subscription.tokens_total_charged, 100);
+        assert_eq!(subscription.next_charge_at, START + 30 * TICKS_PER_SECOND);
+        let stats: Stats = contract.stats.get().unwrap().into();
+        let token_stats = &stats.dao_tokens[&token_id()];
+        assert_eq!(token_stats.total_commission_collected, 1);
+        assert_eq!(token_stats.tvl, 0);
+
+        set_context(bob(), START + 10 * TICKS_PER_SECOND);
+        assert_eq!(
+            contract
+                .charge_subscription_op(&bob(), subscription_id, None)
+                .err(),
+            Some(ContractError::ChargePeriodNotPassed {
+                timestamp: START + 30 * TICKS_PER_SECOND
+            })
+        );
+
+        // Missed periods are not accumulated
+        set_context(bob(), START + 75 * TICKS_PER_SECOND);
+        assert_eq!(
+            contract
+                .charge_subscription_op(&carol(), subscription_id, None)
+                .err(),
+            Some(ContractError::CallerIsNotMerchant {
+                expected: bob(),
+                received: carol(),
+            })
+        );
+        assert_eq!(
+            contract
+                .charge_subscription_op(&bob(), subscription_id, Some(200))
+                .err(),
+            Some(ContractError::InvalidTokenWithdrawAmount {
+                requested: 200,
+                left: 100,
+            })
+        );
+        contract
+            .charge_subscription_op(&bob(), subscription_id, Some(60))
+            .unwrap();
+        let subscription = contract.view_subscription(&subscription_id).unwrap();
+        assert_eq!(subscription.tokens_total_charged, 160);
+        assert_eq!(subscription.next_charge_at, START + 90 * TICKS_PER_SECOND);
+
+        set_context(bob(), START + 90 * TICKS_PER_SECOND);
+        assert_eq!(
+            contract
+                .charge_subscription_op(&bob(), subscription_id, None)
+                .err(),
+            Some(ContractError::InvalidTokenWithdrawAmount {
+                requested: 100,
+                left: 90,
+            })
+        );
+    }
+
+    #[test]
+    fn test_cancel_subscription() {
+        let (mut contract, subscription_id) = new_contract();
+
+        assert_eq!(
+            contract.cancel_subscription_op(&bob(), subscription_id),
+            Err(ContractError::CallerIsNotSubscriber {
+                expected: alice(),
+                received: bob(),
+            })
+        );
+        contract
+            .cancel_subscription_op(&alice(), subscription_id)
+            .unwrap();
+        assert!(contract.view_subscription(&subscription_id).is_err());
+        assert_eq!(
+            contract.view_account(&alice(), true).unwrap().storage_usage,
+            0
+        );
+        assert!(contract
+            .get_merchant_subscriptions(bob(), None, None)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_create_subscription_not_registered() {
+        let (mut contract, _) = new_contract();
+        contract
+            .fund_treasury_op(&carol(), token_id(), 100)
+            .unwrap();
+        assert_eq!(
+            contract.create_subscription_op(&carol(), bob(), token_id(), 100, 30),
+            Err(ContractError::AccountNotRegistered {
+                account_id: carol()
+            })
+        );
+        assert_eq!(
+            contract.create_subscription_op(&alice(), bob(), token_id(), 100, 0),
+            Err(ContractError::InvalidSubscriptionPeriod)
+        );
+    }
+}
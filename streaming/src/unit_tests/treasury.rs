@@ -0,0 +1,257 @@
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use crate::*;
+    use near_sdk::test_utils::test_env::{alice, bob, carol};
+    use near_sdk::{test_utils::VMContextBuilder, testing_env};
+
+    const START: Timestamp = 1_000 * TICKS_PER_SECOND;
+
+    fn finance_id() -> AccountId {
+        "finance.near".parse().unwrap()
+    }
+    fn dao_id() -> AccountId {
+        "dao.near".parse().unwrap()
+    }
+    fn utility_token_id() -> AccountId {
+        "utilitytoken.near".parse().unwrap()
+    }
+    fn token_id() -> AccountId {
+        "token.near".parse().unwrap()
+    }
+
+    fn set_context(predecessor_id: AccountId, timestamp: Timestamp) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor_id)
+            .block_timestamp(timestamp)
+            .build());
+    }
+
+    fn treasury_request(receiver_id: AccountId, balance: Balance) -> CreateRequest {
+        CreateRequest {
+            description: None,
+            owner_id: alice(),
+            receiver_id,
+            tokens_per_sec: U128(1),
+            cliff_period_sec: None,
+            is_auto_start_enabled: None,
+            is_expirable: None,
+            is_locked: None,
+            balance: Some(U128(balance)),
+            funded_from_treasury: Some(true),
+            approver_id: None,
+            milestones: None,
+            cancel_policy: None,
+            is_clawback_allowed: None,
+            arbiter_id: None,
+        }
+    }
+
+    fn new_contract() -> Contract {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(dao_id())
+            .attached_deposit(1)
+            .build());
+        let mut token = Token::new_unlisted(&token_id());
+        token.is_payment = true;
+        contract.dao_update_token(token).unwrap();
+        contract
+    }
+
+    fn alice_treasury(contract: &Contract) -> Treasury {
+        contract
+            .view_account(&alice(), true)
+            .unwrap()
+            .view_treasury(&token_id())
+    }
+
+    #[test]
+    fn test_treasury_settle() {
+        let mut treasury = Treasury {
+            balance: 10,
+            outflow: 2,
+            accrual_remainder: 0,
+            last_update: START,
+            exhausted_at: None,
+        };
+        treasury.settle(START + 3 * TICKS_PER_SECOND);
+        assert_eq!(treasury.balance, 4);
+        assert!(treasury.is_solvent());
+
+        treasury.settle(START + 10 * TICKS_PER_SECOND);
+        assert_eq!(treasury.balance, 0);
+        // The last moment 4 tokens are enough for 2 streams
+        assert_eq!(
+            treasury.exhausted_at,
+            Some(START + 5 * TICKS_PER_SECOND + TICKS_PER_SECOND / 2 - 1)
+        );
+        assert!(!treasury.is_solvent());
+
+        // Nothing changes once exhausted
+        treasury.balance = 7;
+        treasury.settle(START + 20 * TICKS_PER_SECOND);
+        assert_eq!(treasury.balance, 7);
+        assert_eq!(treasury.last_update, START + 20 * TICKS_PER_SECOND);
+    }
+
+    #[test]
+    fn test_treasury_exhausted() {
+        let mut contract = new_contract();
+        set_context(token_id(), START);
+        let stream_ids = contract
+            .create_streams_op(
+                alice(),
+                token_id(),
+                4,
+                vec![treasury_request(bob(), 3), treasury_request(carol(), 1)],
+            )
+            .unwrap();
+        let treasury = alice_treasury(&contract);
+        assert_eq!(treasury.balance, 4);
+        assert_eq!(treasury.outflow, 2);
+        for stream_id in stream_ids.iter() {
+            let stream = contract.view_stream(stream_id).unwrap();
+            assert!(stream.funded_from_treasury);
+            assert_eq!(stream.balance, 0);
+            assert_eq!(stream.status, StreamStatus::Active);
+        }
+
+        set_context(bob(), START + TICKS_PER_SECOND);
+        contract.withdraw_op(&bob(), stream_ids[0]).unwrap();
+        let stream = contract.view_stream(&stream_ids[0]).unwrap();
+        assert_eq!(stream.tokens_total_withdrawn, 1);
+        assert_eq!(stream.balance, 0);
+        assert_eq!(stream.status, StreamStatus::Active);
+        let treasury = alice_treasury(&contract);
+        assert_eq!(treasury.balance, 2);
+
+        // The treasury is exhausted at START + 2.5 sec for both streams
+        set_context(carol(), START + 5 * TICKS_PER_SECOND);
+        let stream = contract.view_stream(&stream_ids[0]).unwrap();
+        assert_eq!(contract.treasury_available_to_withdraw(&stream), 1);
+        contract.withdraw_op(&carol(), stream_ids[1]).unwrap();
+        let stream = contract.view_stream(&stream_ids[1]).unwrap();
+        assert_eq!(stream.tokens_total_withdrawn, 2);
+        assert_eq!(
+            stream.status,
+            StreamStatus::Finished {
+                reason: StreamFinishReason::FinishedBecauseTreasuryExhausted
+            }
+        );
+
+        set_context(bob(), START + 6 * TICKS_PER_SECOND);
+        contract.withdraw_op(&bob(), stream_ids[0]).unwrap();
+        let stream = contract.view_stream(&stream_ids[0]).unwrap();
+        assert_eq!(stream.tokens_total_withdrawn, 2);
+        assert!(stream.status.is_terminated());
+
+        let treasury = alice_treasury(&contract);
+        assert_eq!(treasury.balance, 0);
+        assert_eq!(treasury.outflow, 0);
+        assert!(treasury.exhausted_at.is_none());
+    }
+
+    #[test]
+    fn test_treasury_forecast() {
+        let mut contract = new_contract();
+        set_context(token_id(), START);
+        contract
+            .create_streams_op(
+                alice(),
+                token_id(),
+                4,
+                vec![treasury_request(bob(), 3), treasury_request(carol(), 1)],
+            )
+            .unwrap();
+        // View methods take the contract by value
+        let clone = |contract: &Contract| {
+            Contract::try_from_slice(&contract.try_to_vec().unwrap()).unwrap()
+        };
+
+        // Streams have no balance, the treasury is shared by them
+        let forecast = clone(&contract)
+            .get_account_forecast(alice(), START + TICKS_PER_SECOND)
+            .unwrap();
+        assert_eq!(forecast[&token_id()].outgoing_remaining, 2);
+        assert_eq!(forecast[&token_id()].outgoing_streams.len(), 2);
+        for stream_forecast in forecast[&token_id()].outgoing_streams.iter() {
+            assert_eq!(stream_forecast.run_out_at, START + 2 * TICKS_PER_SECOND);
+        }
+        assert_eq!(
+            forecast[&token_id()].runs_dry_at,
+            Some(START + 2 * TICKS_PER_SECOND)
+        );
+        let forecast = clone(&contract)
+            .get_account_forecast(bob(), START + TICKS_PER_SECOND)
+            .unwrap();
+        assert_eq!(forecast[&token_id()].receivable, 1);
+
+        // Nothing is accrued after the treasury is exhausted
+        let forecast = clone(&contract)
+            .get_account_forecast(alice(), START + 5 * TICKS_PER_SECOND)
+            .unwrap();
+        assert_eq!(forecast[&token_id()].outgoing_remaining, 0);
+        let forecast = contract
+            .get_account_forecast(bob(), START + 5 * TICKS_PER_SECOND)
+            .unwrap();
+        assert_eq!(forecast[&token_id()].receivable, 2);
+    }
+
+    #[test]
+    fn test_treasury_fund_and_start() {
+        let mut contract = new_contract();
+        set_context(token_id(), START);
+        let mut request = treasury_request(bob(), 1);
+        request.is_auto_start_enabled = Some(false);
+        let stream_id = contract
+            .create_streams_op(alice(), token_id(), 1, vec![request])
+            .unwrap()[0];
+        let treasury = alice_treasury(&contract);
+        assert_eq!(treasury.balance, 1);
+        assert_eq!(treasury.outflow, 0);
+
+        contract.fund_treasury_op(&alice(), token_id(), 10).unwrap();
+        set_context(alice(), START + TICKS_PER_SECOND);
+        contract.start_stream_op(&alice(), stream_id).unwrap();
+        let treasury = alice_treasury(&contract);
+        assert_eq!(treasury.balance, 11);
+        assert_eq!(treasury.outflow, 1);
+
+        set_context(alice(), START + 5 * TICKS_PER_SECOND);
+        contract
+            .withdraw_treasury_op(&alice(), token_id(), Some(3))
+            .unwrap();
+        assert_eq!(
+            contract
+                .withdraw_treasury_op(&alice(), token_id(), Some(10))
+                .err(),
+            Some(ContractError::InvalidTokenWithdrawAmount {
+                requested: 10,
+                left: 4,
+            })
+        );
+        assert_eq!(
+            contract.deposit_op(token_id(), stream_id, 10),
+            Err(ContractError::StreamFundedFromTreasury { stream_id })
+        );
+    }
+
+    #[test]
+    fn test_treasury_stream_create_errors() {
+        let mut contract = new_contract();
+        set_context(token_id(), START);
+        let mut request = treasury_request(bob(), 1);
+        request.is_locked = Some(true);
+        assert_eq!(
+            contract.create_streams_op(alice(), token_id(), 1, vec![request]),
+            Err(ContractError::CannotLockTreasuryStream)
+        );
+        assert_eq!(
+            contract.create_streams_op(carol(), token_id(), 1, vec![treasury_request(bob(), 1)]),
+            Err(ContractError::CallerIsNotStreamOwner {
+                expected: alice(),
+                received: carol(),
+            })
+        );
+    }
+}
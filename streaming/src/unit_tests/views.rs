@@ -0,0 +1,421 @@
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use crate::*;
+    use near_sdk::test_utils::test_env::{alice, bob, carol};
+    use near_sdk::{test_utils::VMContextBuilder, testing_env, ONE_NEAR};
+
+    fn finance_id() -> AccountId {
+        "finance.near".parse().unwrap()
+    }
+    fn dao_id() -> AccountId {
+        "dao.near".parse().unwrap()
+    }
+    fn utility_token_id() -> AccountId {
+        "utilitytoken.near".parse().unwrap()
+    }
+
+    fn create_stream(
+        contract: &mut Contract,
+        receiver_id: AccountId,
+        token_account_id: AccountId,
+        is_auto_start_enabled: bool,
+    ) -> StreamId {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(token_account_id.clone())
+            .block_timestamp(contract.streams.len() * TICKS_PER_SECOND)
+            .build());
+        contract
+            .create_stream_op(
+                alice(),
+                token_account_id,
+                ONE_NEAR,
+                CreateRequest {
+                    description: None,
+                    owner_id: alice(),
+                    receiver_id,
+                    tokens_per_sec: U128(1),
+                    cliff_period_sec: None,
+                    is_auto_start_enabled: Some(is_auto_start_enabled),
+                    is_expirable: None,
+                    is_locked: None,
+                    balance: None,
+                    funded_from_treasury: None,
+                    approver_id: None,
+                    milestones: None,
+                    cancel_policy: None,
+                    is_clawback_allowed: None,
+                    arbiter_id: None,
+                },
+            )
+            .unwrap();
+        contract
+            .view_account(&alice(), true)
+            .unwrap()
+            .last_created_stream
+            .unwrap()
+    }
+
+    // View methods take the contract by value
+    fn clone(contract: &Contract) -> Contract {
+        Contract::try_from_slice(&contract.try_to_vec().unwrap()).unwrap()
+    }
+
+    fn new_contract() -> (Contract, Vec<StreamId>) {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(ONE_NEAR)
+            .build());
+        contract.account_deposit_near().unwrap();
+        let token_a: AccountId = "a.near".parse().unwrap();
+        let token_b: AccountId = "b.near".parse().unwrap();
+        let stream_ids = vec![
+            create_stream(&mut contract, bob(), token_a.clone(), true),
+            create_stream(&mut contract, carol(), token_a.clone(), true),
+            create_stream(&mut contract, bob(), token_b, true),
+            create_stream(&mut contract, bob(), token_a, false),
+        ];
+        (contract, stream_ids)
+    }
+
+    #[test]
+    fn test_account_streams_paging() {
+        let (contract, _) = new_contract();
+        let streams = clone(&contract).get_account_outgoing_streams(alice(), Some(0), Some(3));
+        assert_eq!(streams.unwrap().len(), 3);
+
+        let streams = contract.get_account_outgoing_streams(alice(), Some(3), Some(3));
+        assert_eq!(streams.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_account_streams_cursor() {
+        let (contract, stream_ids) = new_contract();
+        let mut ids = vec![];
+        let mut cursor = None;
+        loop {
+            let page = clone(&contract)
+                .get_account_streams(alice(), StreamDirection::Outgoing, None, cursor, Some(3))
+                .unwrap();
+            assert_eq!(page.active_streams, 3);
+            assert_eq!(page.inactive_streams, 1);
+            // Streams of the account are not counted by status
+            assert!(serde_json::to_value(&page)
+                .unwrap()
+                .get("status_counts")
+                .is_none());
+            ids.extend(page.streams.into_iter().map(|stream| stream.id));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        ids.sort();
+        let mut stream_ids = stream_ids;
+        stream_ids.sort();
+        assert_eq!(ids, stream_ids);
+    }
+
+    #[test]
+    fn test_account_streams_filter() {
+        let (contract, stream_ids) = new_contract();
+        let page = clone(&contract)
+            .get_account_streams(
+                alice(),
+                StreamDirection::Outgoing,
+                Some(StreamFilter {
+                    status: Some(StreamStatusFilter::Active),
+                    token_account_id: Some("a.near".parse().unwrap()),
+                    counterparty_id: Some(bob()),
+                    ..Default::default()
+                }),
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(page.next_cursor, None);
+        assert_eq!(
+            page.streams.iter().map(|s| s.id).collect::<Vec<_>>(),
+            vec![stream_ids[0]]
+        );
+
+        let page = contract
+            .get_account_streams(
+                bob(),
+                StreamDirection::Incoming,
+                Some(StreamFilter {
+                    status: Some(StreamStatusFilter::Initialized),
+                    ..Default::default()
+                }),
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            page.streams.iter().map(|s| s.id).collect::<Vec<_>>(),
+            vec![stream_ids[3]]
+        );
+    }
+
+    #[test]
+    fn test_filtered_streams_by_creation_time() {
+        let (contract, stream_ids) = new_contract();
+        let page = contract.get_filtered_streams(
+            Some(StreamFilter {
+                created_from: Some(TICKS_PER_SECOND),
+                created_to: Some(3 * TICKS_PER_SECOND),
+                ..Default::default()
+            }),
+            None,
+            Some(1),
+        );
+        assert_eq!(page.active_streams, 3);
+        assert_eq!(page.inactive_streams, 1);
+        assert_eq!(page.streams.len(), 1);
+        assert!(page.next_cursor.is_some());
+        let next_page = contract.get_filtered_streams(
+            Some(StreamFilter {
+                created_from: Some(TICKS_PER_SECOND),
+                created_to: Some(3 * TICKS_PER_SECOND),
+                ..Default::default()
+            }),
+            page.next_cursor,
+            Some(10),
+        );
+        let mut ids = page
+            .streams
+            .iter()
+            .chain(next_page.streams.iter())
+            .map(|s| s.id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        let mut expected = vec![stream_ids[1], stream_ids[2]];
+        expected.sort();
+        assert_eq!(ids, expected);
+    }
+
+    #[test]
+    fn test_filtered_streams_status_counts() {
+        let (mut contract, stream_ids) = new_contract();
+        contract.stop_stream_op(&alice(), stream_ids[1]).unwrap();
+        contract.pause_stream_op(&alice(), stream_ids[2]).unwrap();
+
+        let page = clone(&contract).get_filtered_streams(None, None, None);
+        assert_eq!(page.streams.len(), 4);
+        assert_eq!(page.active_streams, 1);
+        // The finished stream is not inactive
+        assert_eq!(page.inactive_streams, 2);
+        assert_eq!(
+            page.status_counts,
+            Some(StreamStatusCounts {
+                active: 1,
+                initialized: 1,
+                paused: 1,
+                finished: 1,
+                ..Default::default()
+            })
+        );
+
+        // Streams stored before the upgrade are counted once migrated
+        store_legacy_stream(&mut contract, &stream_ids[3]);
+        assert_eq!(contract.stream_status_counts.initialized, 0);
+        contract.migrate_streams_op(0, 10);
+        assert_eq!(contract.stream_status_counts.initialized, 1);
+        contract.migrate_streams_op(0, 10);
+        assert_eq!(contract.stream_status_counts.initialized, 1);
+        assert!(matches!(
+            contract.streams.get(&stream_ids[3]),
+            Some(VStream::Current(_))
+        ));
+    }
+
+    #[test]
+    fn test_status_counts_legacy_stream() {
+        let (mut contract, stream_ids) = new_contract();
+        store_legacy_stream(&mut contract, &stream_ids[3]);
+        assert_eq!(contract.stream_status_counts.initialized, 0);
+
+        // Nothing is decremented for the legacy stream on the action,
+        // so counts don't underflow, and it's counted once saved
+        testing_env!(VMContextBuilder::new().build());
+        contract.start_stream_op(&alice(), stream_ids[3]).unwrap();
+        assert_eq!(
+            contract.stream_status_counts,
+            StreamStatusCounts {
+                active: 4,
+                ..Default::default()
+            }
+        );
+        contract.migrate_streams_op(0, 10);
+        assert_eq!(contract.stream_status_counts.active, 4);
+    }
+
+    // Rewrites the stream as stored before the upgrade, uncounted
+    fn store_legacy_stream(contract: &mut Contract, stream_id: &StreamId) {
+        let stream = contract.extract_stream(stream_id).unwrap();
+        let legacy = StreamV0 {
+            id: stream.id,
+            description: stream.description,
+            creator_id: stream.creator_id,
+            owner_id: stream.owner_id,
+            receiver_id: stream.receiver_id,
+            token_account_id: stream.token_account_id,
+            timestamp_created: stream.timestamp_created,
+            last_action: stream.last_action,
+            balance: stream.balance,
+            tokens_per_sec: stream.tokens_per_sec,
+            status: stream.status,
+            tokens_total_withdrawn: stream.tokens_total_withdrawn,
+            cliff: stream.cliff,
+            is_expirable: stream.is_expirable,
+            is_locked: stream.is_locked,
+        };
+        contract.streams.insert(stream_id, &VStream::V0(legacy));
+    }
+
+    #[test]
+    fn test_token_streams() {
+        let (mut contract, stream_ids) = new_contract();
+        let token_a: AccountId = "a.near".parse().unwrap();
+        assert_eq!(contract.get_token_outflow(token_a.clone()), U128(2));
+        assert_eq!(contract.get_token_outflow(utility_token_id()), U128(0));
+
+        let page = contract.get_token_streams(token_a.clone(), None, None, None);
+        assert_eq!(page.active_streams, 2);
+        assert_eq!(page.inactive_streams, 1);
+        assert_eq!(page.streams.len(), 3);
+        assert!(page.next_cursor.is_none());
+
+        let page = contract.get_token_streams(
+            token_a.clone(),
+            Some(StreamStatusFilter::Initialized),
+            None,
+            None,
+        );
+        assert_eq!(
+            page.streams.iter().map(|s| s.id).collect::<Vec<_>>(),
+            vec![stream_ids[3]]
+        );
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(1)
+            .block_timestamp(4 * TICKS_PER_SECOND)
+            .build());
+        contract.stop_stream_op(&alice(), stream_ids[0]).unwrap();
+        assert_eq!(contract.get_token_outflow(token_a.clone()), U128(1));
+
+        let page =
+            contract.get_token_streams(token_a, Some(StreamStatusFilter::Active), None, None);
+        assert_eq!(page.active_streams, 1);
+        assert_eq!(page.inactive_streams, 1);
+        assert_eq!(
+            page.streams.iter().map(|s| s.id).collect::<Vec<_>>(),
+            vec![stream_ids[1]]
+        );
+    }
+
+    #[test]
+    fn test_stats_history() {
+        let mut contract = Contract::new(dao_id(), finance_id(), utility_token_id(), 18);
+        let token_a: AccountId = "a.near".parse().unwrap();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(dao_id())
+            .attached_deposit(1)
+            .build());
+        let mut token = Token::new_unlisted(&token_a);
+        token.is_payment = true;
+        contract.dao_update_token(token).unwrap();
+
+        let stream_ids = [
+            create_stream(&mut contract, bob(), token_a.clone(), true),
+            create_stream(&mut contract, carol(), token_a.clone(), true),
+        ];
+
+        let day = SECONDS_PER_DAY * TICKS_PER_SECOND;
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(1)
+            .block_timestamp(day + TICKS_PER_SECOND)
+            .build());
+        contract.stop_stream_op(&alice(), stream_ids[0]).unwrap();
+
+        let history = contract.get_stats_history(token_a.clone(), 0, 1);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].day, 0);
+        assert_eq!(history[0].streams_created, 2);
+        assert_eq!(history[0].deposits, 2 * ONE_NEAR);
+        assert_eq!(history[0].peak_active_streams, 2);
+        assert_eq!(history[1].day, 1);
+        assert_eq!(history[1].streams_created, 0);
+        assert_eq!(history[1].peak_active_streams, 2);
+        assert_eq!(history[1].transferred + history[1].refunded, ONE_NEAR);
+        assert!(history[1].transferred > 0);
+
+        // The bucket of day 0 is reused a year later
+        let history = contract.get_stats_history(token_a, STATS_HISTORY_DAYS, STATS_HISTORY_DAYS);
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn test_account_forecast() {
+        let (contract, _) = new_contract();
+        let token_a: AccountId = "a.near".parse().unwrap();
+        let token_b: AccountId = "b.near".parse().unwrap();
+
+        let forecast = clone(&contract)
+            .get_account_forecast(bob(), 10 * TICKS_PER_SECOND)
+            .unwrap();
+        assert_eq!(forecast.len(), 2);
+        assert_eq!(forecast[&token_a].receivable, 10);
+        assert_eq!(forecast[&token_b].receivable, 8);
+        assert!(forecast[&token_a].outgoing_streams.is_empty());
+        assert!(forecast[&token_a].runs_dry_at.is_none());
+
+        let forecast = contract
+            .get_account_forecast(alice(), 10 * TICKS_PER_SECOND)
+            .unwrap();
+        assert_eq!(forecast[&token_a].receivable, 0);
+        assert_eq!(forecast[&token_a].outgoing_remaining, 2 * ONE_NEAR - 10 - 9);
+        assert_eq!(forecast[&token_a].outgoing_streams.len(), 2);
+        assert_eq!(forecast[&token_b].outgoing_remaining, ONE_NEAR - 8);
+        assert_eq!(
+            forecast[&token_b].runs_dry_at,
+            Some(forecast[&token_b].outgoing_streams[0].run_out_at)
+        );
+    }
+
+    #[test]
+    fn test_account_forecast_stop_scheduled() {
+        let (mut contract, stream_ids) = new_contract();
+        let token_b: AccountId = "b.near".parse().unwrap();
+        let mut stream = contract.extract_stream(&stream_ids[2]).unwrap();
+        stream.cancel_policy = Some(CancelPolicy {
+            no_stop_before: None,
+            notice_period_sec: Some(18),
+            severance: 100,
+        });
+        stream.stop_at = Some(20 * TICKS_PER_SECOND);
+        contract.save_stream(stream).unwrap();
+
+        let forecast = clone(&contract)
+            .get_account_forecast(alice(), 10 * TICKS_PER_SECOND)
+            .unwrap();
+        assert_eq!(forecast[&token_b].outgoing_remaining, 10 + 100);
+        assert_eq!(forecast[&token_b].runs_dry_at, Some(20 * TICKS_PER_SECOND));
+
+        // Capped at the scheduled stop
+        let forecast = clone(&contract)
+            .get_account_forecast(alice(), 30 * TICKS_PER_SECOND)
+            .unwrap();
+        assert_eq!(forecast[&token_b].outgoing_remaining, 0);
+        assert_eq!(
+            forecast[&token_b].outgoing_streams[0].run_out_at,
+            20 * TICKS_PER_SECOND
+        );
+        let forecast = contract
+            .get_account_forecast(bob(), 30 * TICKS_PER_SECOND)
+            .unwrap();
+        assert_eq!(forecast[&token_b].receivable, 18);
+    }
+}
@@ -122,6 +122,8 @@ impl Contract {
             );
         } else if path == "/stats.rs" {
             return Web4Response::plain(include_str!("../stats.rs").to_string());
+        } else if path == "/storage.rs" {
+            return Web4Response::plain(include_str!("../storage.rs").to_string());
         } else if path == "/stream.rs" {
             return Web4Response::plain(include_str!("../stream.rs").to_string());
         } else if path == "/stream_ops.rs" {
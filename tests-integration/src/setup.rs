@@ -581,7 +581,7 @@ impl Env {
             &owner,
             amount,
             &serde_json::to_string(&TransferCallRequest::Create {
-                request: CreateRequest {
+                request: Box::new(CreateRequest {
                     owner_id: owner.account_id(),
                     receiver_id: receiver.account_id(),
                     tokens_per_sec,
@@ -590,7 +590,14 @@ impl Env {
                     is_auto_start_enabled,
                     is_expirable,
                     is_locked,
-                },
+                    balance: None,
+                    funded_from_treasury: None,
+                    approver_id: None,
+                    milestones: None,
+                    cancel_policy: None,
+                    is_clawback_allowed: None,
+                    arbiter_id: None,
+                }),
             })
             .unwrap(),
         )